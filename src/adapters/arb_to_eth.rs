@@ -0,0 +1,123 @@
+use alloy::primitives::{Address, FixedBytes, U256};
+use alloy::providers::Provider;
+use alloy::sol_types::SolCall;
+use async_trait::async_trait;
+
+use crate::adapters::{RelayPayload, RouteAdapter};
+use crate::config::Route;
+use crate::contracts::{Claim, IVeaInboxArbToEth, IVeaOutboxArbToEth, IVeaOutboxOnL1};
+use crate::tasks::fees::FeeBid;
+use crate::tasks::{send_tx, TaskError};
+
+/// Outbox on the Arbitrum settlement chain; deposits are paid in the native currency.
+pub struct ArbToEthAdapter;
+
+#[async_trait]
+impl RouteAdapter for ArbToEthAdapter {
+    fn deposit_currency(&self, route: &Route) -> String {
+        route.outbox_native_currency.clone()
+    }
+
+    async fn deposit_balance(
+        &self,
+        route: &Route,
+        wallet: Address,
+    ) -> Result<U256, TaskError> {
+        Ok(route.outbox_provider.get_balance(wallet).await?)
+    }
+
+    async fn gas_balance(
+        &self,
+        _route: &Route,
+        _wallet: Address,
+    ) -> Result<Option<U256>, TaskError> {
+        Ok(None)
+    }
+
+    async fn deposit_allowance(
+        &self,
+        _route: &Route,
+        _wallet: Address,
+    ) -> Result<Option<U256>, TaskError> {
+        Ok(None)
+    }
+
+    async fn prepare_deposit(&self, _route: &Route) -> Result<(), TaskError> {
+        Ok(())
+    }
+
+    async fn estimate_claim_gas(
+        &self,
+        route: &Route,
+        epoch: u64,
+        state_root: FixedBytes<32>,
+        deposit: U256,
+    ) -> Result<u64, TaskError> {
+        let outbox = IVeaOutboxArbToEth::new(route.outbox_address, route.outbox_provider.clone());
+        let call = outbox.claim(U256::from(epoch), state_root).value(deposit).from(route.outbox_nonces.wallet());
+        call.estimate_gas().await.map_err(|e| TaskError::from(e).or_race_lost())
+    }
+
+    async fn claim(
+        &self,
+        route: &Route,
+        epoch: u64,
+        state_root: FixedBytes<32>,
+        deposit: U256,
+        bid: FeeBid,
+    ) -> Result<(), TaskError> {
+        let outbox = IVeaOutboxArbToEth::new(route.outbox_address, route.outbox_provider.clone());
+        send_tx(
+            outbox.claim(U256::from(epoch), state_root).value(deposit),
+            &route.outbox_nonces,
+            bid,
+            "claim",
+            &route.name,
+        ).await.map_err(TaskError::or_race_lost)
+    }
+
+    async fn challenge(
+        &self,
+        route: &Route,
+        epoch: u64,
+        claim: Claim,
+        deposit: U256,
+        bid: FeeBid,
+    ) -> Result<(), TaskError> {
+        let outbox = IVeaOutboxArbToEth::new(route.outbox_address, route.outbox_provider.clone());
+        send_tx(
+            outbox.challenge(U256::from(epoch), claim).value(deposit),
+            &route.outbox_nonces,
+            bid,
+            "challenge",
+            &route.name,
+        ).await.map_err(TaskError::or_race_lost)
+    }
+
+    async fn send_snapshot(
+        &self,
+        route: &Route,
+        epoch: u64,
+        claim: Claim,
+        bid: FeeBid,
+    ) -> Result<(), TaskError> {
+        let inbox = IVeaInboxArbToEth::new(route.inbox_address, route.inbox_provider.clone());
+        send_tx(
+            inbox.sendSnapshot(U256::from(epoch), claim),
+            &route.inbox_nonces,
+            bid,
+            "sendSnapshot",
+            &route.name,
+        ).await
+    }
+
+    /// `resolveDisputedClaim(epoch, snapshot, claim)` on the outbox.
+    fn decode_relay(&self, data: &[u8]) -> Option<RelayPayload> {
+        let call = IVeaOutboxOnL1::resolveDisputedClaimCall::abi_decode(data).ok()?;
+        Some(RelayPayload { epoch: call._epoch.try_into().ok()?, state_root: call._stateRoot, claim: call._claim })
+    }
+
+    async fn relay_destination(&self, route: &Route) -> Result<Address, TaskError> {
+        Ok(route.outbox_address)
+    }
+}
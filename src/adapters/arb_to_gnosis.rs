@@ -0,0 +1,149 @@
+use alloy::primitives::{Address, FixedBytes, U256};
+use alloy::providers::Provider;
+use alloy::sol_types::SolCall;
+use async_trait::async_trait;
+use tracing::{info, warn};
+
+use crate::adapters::{RelayPayload, RouteAdapter};
+use crate::config::Route;
+use crate::contracts::{Claim, IRouterArbToGnosis, IVeaInboxArbToGnosis, IVeaOutboxArbToGnosis, IWETH};
+use crate::tasks::fees::FeeBid;
+use crate::tasks::{send_tx, TaskError};
+
+const SEND_SNAPSHOT_GAS_LIMIT: u64 = 500000;
+
+/// Outbox bridged through the AMB; deposits are paid in the route's WETH token,
+/// which the outbox pulls via `transferFrom`.
+pub struct ArbToGnosisAdapter;
+
+/// Config validation requires the token, so this only fails for a route
+/// built by hand.
+fn weth_address(route: &Route) -> Result<Address, TaskError> {
+    route.weth_address
+        .ok_or_else(|| TaskError::Fatal(format!("{} route without a deposit token", route.name)))
+}
+
+#[async_trait]
+impl RouteAdapter for ArbToGnosisAdapter {
+    fn deposit_currency(&self, _route: &Route) -> String {
+        "WETH".to_string()
+    }
+
+    async fn deposit_balance(
+        &self,
+        route: &Route,
+        wallet: Address,
+    ) -> Result<U256, TaskError> {
+        let weth = IWETH::new(weth_address(route)?, route.outbox_provider.clone());
+        Ok(weth.balanceOf(wallet).call().await?)
+    }
+
+    async fn gas_balance(
+        &self,
+        route: &Route,
+        wallet: Address,
+    ) -> Result<Option<U256>, TaskError> {
+        Ok(Some(route.outbox_provider.get_balance(wallet).await?))
+    }
+
+    async fn deposit_allowance(
+        &self,
+        route: &Route,
+        wallet: Address,
+    ) -> Result<Option<U256>, TaskError> {
+        let weth = IWETH::new(weth_address(route)?, route.outbox_provider.clone());
+        Ok(Some(weth.allowance(wallet, route.outbox_address).call().await?))
+    }
+
+    /// Approves the outbox for the maximum amount if it has no allowance yet.
+    async fn prepare_deposit(&self, route: &Route) -> Result<(), TaskError> {
+        let allowance = self.deposit_allowance(route, route.outbox_nonces.wallet()).await?.unwrap_or_default();
+        if allowance != U256::ZERO {
+            info!(route = %route.name, allowance = %allowance, "WETH approval already exists");
+            return Ok(());
+        }
+        warn!(route = %route.name, "No WETH approval found for outbox. Setting max approval...");
+        let weth = IWETH::new(weth_address(route)?, route.outbox_provider.clone());
+        send_tx(weth.approve(route.outbox_address, U256::MAX), &route.outbox_nonces, FeeBid::default(), "approve", &route.name).await?;
+        info!(route = %route.name, "WETH max approval set for outbox");
+        Ok(())
+    }
+
+    async fn estimate_claim_gas(
+        &self,
+        route: &Route,
+        epoch: u64,
+        state_root: FixedBytes<32>,
+        _deposit: U256,
+    ) -> Result<u64, TaskError> {
+        let outbox = IVeaOutboxArbToGnosis::new(route.outbox_address, route.outbox_provider.clone());
+        let call = outbox.claim(U256::from(epoch), state_root).from(route.outbox_nonces.wallet());
+        call.estimate_gas().await.map_err(|e| TaskError::from(e).or_race_lost())
+    }
+
+    async fn claim(
+        &self,
+        route: &Route,
+        epoch: u64,
+        state_root: FixedBytes<32>,
+        _deposit: U256,
+        bid: FeeBid,
+    ) -> Result<(), TaskError> {
+        let outbox = IVeaOutboxArbToGnosis::new(route.outbox_address, route.outbox_provider.clone());
+        send_tx(
+            outbox.claim(U256::from(epoch), state_root),
+            &route.outbox_nonces,
+            bid,
+            "claim",
+            &route.name,
+        ).await.map_err(TaskError::or_race_lost)
+    }
+
+    async fn challenge(
+        &self,
+        route: &Route,
+        epoch: u64,
+        claim: Claim,
+        _deposit: U256,
+        bid: FeeBid,
+    ) -> Result<(), TaskError> {
+        let outbox = IVeaOutboxArbToGnosis::new(route.outbox_address, route.outbox_provider.clone());
+        send_tx(
+            outbox.challenge(U256::from(epoch), claim),
+            &route.outbox_nonces,
+            bid,
+            "challenge",
+            &route.name,
+        ).await.map_err(TaskError::or_race_lost)
+    }
+
+    async fn send_snapshot(
+        &self,
+        route: &Route,
+        epoch: u64,
+        claim: Claim,
+        bid: FeeBid,
+    ) -> Result<(), TaskError> {
+        let inbox = IVeaInboxArbToGnosis::new(route.inbox_address, route.inbox_provider.clone());
+        send_tx(
+            inbox.sendSnapshot(U256::from(epoch), U256::from(SEND_SNAPSHOT_GAS_LIMIT), claim),
+            &route.inbox_nonces,
+            bid,
+            "sendSnapshot",
+            &route.name,
+        ).await
+    }
+
+    /// `route(epoch, snapshot, gasLimit, claim)` on the L1 router, which
+    /// forwards it to the Gnosis outbox.
+    fn decode_relay(&self, data: &[u8]) -> Option<RelayPayload> {
+        let call = IRouterArbToGnosis::routeCall::abi_decode(data).ok()?;
+        Some(RelayPayload { epoch: call._epoch.try_into().ok()?, state_root: call._stateroot, claim: call._claim })
+    }
+
+    /// The router the inbox was deployed with, not the Gnosis outbox.
+    async fn relay_destination(&self, route: &Route) -> Result<Address, TaskError> {
+        let inbox = IVeaInboxArbToGnosis::new(route.inbox_address, route.inbox_provider.clone());
+        Ok(inbox.routerArbToGnosis().call().await?)
+    }
+}
@@ -0,0 +1,142 @@
+pub mod arb_to_eth;
+pub mod arb_to_gnosis;
+
+use alloy::primitives::{Address, FixedBytes, U256};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::config::{Route, RouteContracts};
+use crate::contracts::Claim;
+use crate::tasks::fees::FeeBid;
+use crate::tasks::TaskError;
+
+pub use arb_to_eth::ArbToEthAdapter;
+pub use arb_to_gnosis::ArbToGnosisAdapter;
+
+/// What a `sendSnapshot` asks L1 to resolve, decoded from the data of its
+/// `L2ToL1Tx`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayPayload {
+    pub epoch: u64,
+    pub state_root: FixedBytes<32>,
+    pub claim: Claim,
+}
+
+/// What an `L2ToL1Tx` must carry for us to relay it.
+#[derive(Debug, Clone)]
+pub struct ExpectedRelay {
+    /// Our L1 contract, from `relay_destination`.
+    pub destination: Address,
+    pub epoch: u64,
+    /// The inbox snapshot for `epoch`.
+    pub state_root: FixedBytes<32>,
+    /// The claim we indexed, if `epoch` is inside the sync window.
+    pub claim: Option<Claim>,
+}
+
+impl ExpectedRelay {
+    /// Checks that a relay of `data` to `destination` resolves our epoch with
+    /// the inbox snapshot and the indexed claim. The outbox checks the claim
+    /// against its hash, so a relay with any other claim would revert.
+    pub fn check(&self, adapter: &dyn RouteAdapter, destination: Address, data: &[u8]) -> Result<(), String> {
+        if destination != self.destination {
+            return Err(format!("destination {} is not {}", destination, self.destination));
+        }
+        let payload = adapter.decode_relay(data).ok_or("data is not a snapshot relay")?;
+        if payload.epoch != self.epoch {
+            return Err(format!("data resolves epoch {}", payload.epoch));
+        }
+        if payload.state_root != self.state_root {
+            return Err(format!("state root {} is not the inbox snapshot {}", payload.state_root, self.state_root));
+        }
+        if let Some(claim) = &self.claim && payload.claim != *claim {
+            return Err(format!("claim {:?} is not the indexed {:?}", payload.claim, claim));
+        }
+        Ok(())
+    }
+}
+
+/// Contract-family specific calls. Everything that differs between outbox
+/// flavours lives behind this trait so tasks never branch on the route.
+#[async_trait]
+pub trait RouteAdapter: Send + Sync {
+    /// Currency deposits are paid in, for logs and startup checks.
+    fn deposit_currency(&self, route: &Route) -> String;
+
+    /// Balance the wallet can put up as a claim or challenge deposit.
+    async fn deposit_balance(
+        &self,
+        route: &Route,
+        wallet: Address,
+    ) -> Result<U256, TaskError>;
+
+    /// Native balance paying for outbox gas, when deposits come out of a
+    /// different balance.
+    async fn gas_balance(
+        &self,
+        route: &Route,
+        wallet: Address,
+    ) -> Result<Option<U256>, TaskError>;
+
+    /// How much of the deposit token the outbox may pull, for flavours that
+    /// pull deposits with `transferFrom`.
+    async fn deposit_allowance(
+        &self,
+        route: &Route,
+        wallet: Address,
+    ) -> Result<Option<U256>, TaskError>;
+
+    /// Whatever must happen once before the wallet can put up deposits,
+    /// e.g. approving the outbox to pull them.
+    async fn prepare_deposit(&self, route: &Route) -> Result<(), TaskError>;
+
+    /// Gas `claim` would use, for the claim policy's cost estimate.
+    async fn estimate_claim_gas(
+        &self,
+        route: &Route,
+        epoch: u64,
+        state_root: FixedBytes<32>,
+        deposit: U256,
+    ) -> Result<u64, TaskError>;
+
+    async fn claim(
+        &self,
+        route: &Route,
+        epoch: u64,
+        state_root: FixedBytes<32>,
+        deposit: U256,
+        bid: FeeBid,
+    ) -> Result<(), TaskError>;
+
+    async fn challenge(
+        &self,
+        route: &Route,
+        epoch: u64,
+        claim: Claim,
+        deposit: U256,
+        bid: FeeBid,
+    ) -> Result<(), TaskError>;
+
+    async fn send_snapshot(
+        &self,
+        route: &Route,
+        epoch: u64,
+        claim: Claim,
+        bid: FeeBid,
+    ) -> Result<(), TaskError>;
+
+    /// Decodes the L1 calldata `send_snapshot` makes the inbox emit.
+    fn decode_relay(&self, data: &[u8]) -> Option<RelayPayload>;
+
+    /// L1 contract that calldata is addressed to.
+    async fn relay_destination(&self, route: &Route) -> Result<Address, TaskError>;
+}
+
+impl RouteContracts {
+    pub fn adapter(self) -> Arc<dyn RouteAdapter> {
+        match self {
+            RouteContracts::ArbToEth => Arc::new(ArbToEthAdapter),
+            RouteContracts::ArbToGnosis => Arc::new(ArbToGnosisAdapter),
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{info, warn};
+
+use crate::tasks::{ClaimStore, Task, TaskStore};
+use crate::sync::LockExt;
+
+const MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+struct RouteStores {
+    task_store: Arc<Mutex<TaskStore>>,
+    claim_store: Arc<Mutex<ClaimStore>>,
+}
+
+/// Shared handles the admin API operates on - the same store mutexes the
+/// dispatcher, indexer and epoch watcher lock.
+pub struct AdminState {
+    routes: BTreeMap<String, RouteStores>,
+    make_claims: Arc<AtomicBool>,
+}
+
+struct RpcError(i64, String);
+
+impl AdminState {
+    pub fn new(make_claims: Arc<AtomicBool>) -> Self {
+        Self { routes: BTreeMap::new(), make_claims }
+    }
+
+    pub fn add_route(&mut self, name: &str, task_store: Arc<Mutex<TaskStore>>, claim_store: Arc<Mutex<ClaimStore>>) {
+        self.routes.insert(name.to_string(), RouteStores { task_store, claim_store });
+    }
+
+    /// Handles one JSON-RPC 2.0 request body and returns the response body.
+    pub fn handle_request(&self, body: &str) -> String {
+        let request: Value = match serde_json::from_str(body) {
+            Ok(v) => v,
+            Err(e) => return error_response(Value::Null, PARSE_ERROR, &e.to_string()),
+        };
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            return error_response(id, INVALID_REQUEST, "Missing method");
+        };
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        match self.call(method, &params) {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}).to_string(),
+            Err(RpcError(code, message)) => error_response(id, code, &message),
+        }
+    }
+
+    fn call(&self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "routes_list" => {
+                let routes: Vec<Value> = self.routes.iter()
+                    .map(|(name, stores)| {
+                        let state = stores.task_store.locked().load();
+                        json!({
+                            "route": name,
+                            "on_sync": state.on_sync,
+                            "pending_tasks": state.tasks.len(),
+                            "dead_letters": state.dead_letters.len(),
+                            "missed_deadlines": state.missed_deadlines.len(),
+                        })
+                    })
+                    .collect();
+                Ok(json!(routes))
+            }
+            "tasks_list" => {
+                let stores = self.route(params)?;
+                let state = stores.task_store.locked().load();
+                Ok(json!(state.tasks))
+            }
+            "claims_get" => {
+                let stores = self.route(params)?;
+                let epoch = u64_param(params, "epoch")?;
+                let claim_store = stores.claim_store.locked();
+                if !claim_store.exists(epoch) {
+                    return Ok(Value::Null);
+                }
+                Ok(json!(claim_store.get(epoch)))
+            }
+            "tasks_run_now" => {
+                let stores = self.route(params)?;
+                let task_store = stores.task_store.locked();
+                let task = find_task(&task_store, params)?;
+                task_store.reschedule_task(&task, 0);
+                info!(epoch = task.epoch, kind = task.kind.name(), "Admin: task forced to run on next poll");
+                Ok(json!(true))
+            }
+            "tasks_reschedule" => {
+                let stores = self.route(params)?;
+                let execute_after = u64_param(params, "execute_after")?;
+                let task_store = stores.task_store.locked();
+                let task = find_task(&task_store, params)?;
+                task_store.reschedule_task(&task, execute_after);
+                info!(epoch = task.epoch, kind = task.kind.name(), execute_after, "Admin: task rescheduled");
+                Ok(json!(true))
+            }
+            "tasks_drop" => {
+                let stores = self.route(params)?;
+                let task_store = stores.task_store.locked();
+                let task = find_task(&task_store, params)?;
+                task_store.remove_task(&task);
+                warn!(epoch = task.epoch, kind = task.kind.name(), "Admin: task dropped");
+                Ok(json!(true))
+            }
+            "dead_letters_list" => {
+                let stores = self.route(params)?;
+                let state = stores.task_store.locked().load();
+                Ok(json!(state.dead_letters))
+            }
+            "missed_deadlines_list" => {
+                let stores = self.route(params)?;
+                let state = stores.task_store.locked().load();
+                Ok(json!(state.missed_deadlines))
+            }
+            "dead_letters_requeue" => {
+                let stores = self.route(params)?;
+                let execute_after = params.get("execute_after").and_then(Value::as_u64).unwrap_or(0);
+                let task_store = stores.task_store.locked();
+                let task = find_dead_letter(&task_store, params)?;
+                task_store.requeue_dead_letter(&task, execute_after);
+                info!(epoch = task.epoch, kind = task.kind.name(), execute_after, "Admin: dead-lettered task requeued");
+                Ok(json!(true))
+            }
+            "dead_letters_drop" => {
+                let stores = self.route(params)?;
+                let task_store = stores.task_store.locked();
+                let task = find_dead_letter(&task_store, params)?;
+                task_store.remove_dead_letter(&task);
+                warn!(epoch = task.epoch, kind = task.kind.name(), "Admin: dead-lettered task dropped");
+                Ok(json!(true))
+            }
+            "make_claims_get" => Ok(json!(self.make_claims.load(Ordering::Relaxed))),
+            "make_claims_set" => {
+                let enabled = params.get("enabled").and_then(Value::as_bool)
+                    .ok_or_else(|| RpcError(INVALID_PARAMS, "Missing bool param 'enabled'".to_string()))?;
+                self.make_claims.store(enabled, Ordering::Relaxed);
+                info!(enabled, "Admin: make_claims toggled");
+                Ok(json!(enabled))
+            }
+            _ => Err(RpcError(METHOD_NOT_FOUND, format!("Unknown method {}", method))),
+        }
+    }
+
+    fn route(&self, params: &Value) -> Result<&RouteStores, RpcError> {
+        let name = params.get("route").and_then(Value::as_str)
+            .ok_or_else(|| RpcError(INVALID_PARAMS, "Missing string param 'route'".to_string()))?;
+        self.routes.get(name)
+            .ok_or_else(|| RpcError(SERVER_ERROR, format!("Unknown route {}", name)))
+    }
+}
+
+fn u64_param(params: &Value, name: &str) -> Result<u64, RpcError> {
+    params.get(name).and_then(Value::as_u64)
+        .ok_or_else(|| RpcError(INVALID_PARAMS, format!("Missing integer param '{}'", name)))
+}
+
+fn task_key(params: &Value) -> Result<(u64, &str), RpcError> {
+    let epoch = u64_param(params, "epoch")?;
+    let kind = params.get("kind").and_then(Value::as_str)
+        .ok_or_else(|| RpcError(INVALID_PARAMS, "Missing string param 'kind'".to_string()))?;
+    Ok((epoch, kind))
+}
+
+fn find_task(task_store: &TaskStore, params: &Value) -> Result<Task, RpcError> {
+    let (epoch, kind) = task_key(params)?;
+    task_store.find_task(epoch, kind)
+        .ok_or_else(|| RpcError(SERVER_ERROR, format!("No {} task for epoch {}", kind, epoch)))
+}
+
+fn find_dead_letter(task_store: &TaskStore, params: &Value) -> Result<Task, RpcError> {
+    let (epoch, kind) = task_key(params)?;
+    task_store.find_dead_letter(epoch, kind)
+        .ok_or_else(|| RpcError(SERVER_ERROR, format!("No dead-lettered {} task for epoch {}", kind, epoch)))
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> String {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}}).to_string()
+}
+
+async fn read_body(stream: &mut TcpStream) -> Option<String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if buf.len() > MAX_REQUEST_BYTES {
+            return None;
+        }
+        let Some(header_end) = buf.windows(4).position(|w| w == b"\r\n\r\n") else {
+            continue;
+        };
+        let headers = String::from_utf8_lossy(&buf[..header_end]).to_string();
+        if !headers.starts_with("POST ") {
+            return None;
+        }
+        let content_length = headers.lines()
+            .filter_map(|l| l.split_once(':'))
+            .find(|(k, _)| k.trim().eq_ignore_ascii_case("content-length"))
+            .and_then(|(_, v)| v.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+        let body_start = header_end + 4;
+        while buf.len() < body_start + content_length {
+            let n = stream.read(&mut chunk).await.ok()?;
+            if n == 0 || buf.len() > MAX_REQUEST_BYTES {
+                return None;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        return Some(String::from_utf8_lossy(&buf[body_start..body_start + content_length]).to_string());
+    }
+}
+
+/// Serves the admin JSON-RPC API on `POST /`. Bind it to localhost only.
+pub async fn serve(addr: SocketAddr, state: Arc<AdminState>) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!(addr = %listener.local_addr()?, "Serving admin API");
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let state = state.clone();
+        tokio::spawn(async move {
+            let response = match read_body(&mut stream).await {
+                Some(body) => {
+                    let body = tokio::task::spawn_blocking(move || state.handle_request(&body)).await
+                        .unwrap_or_else(|e| error_response(Value::Null, SERVER_ERROR, &e.to_string()));
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(), body
+                    )
+                }
+                None => "HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+            };
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
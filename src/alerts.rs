@@ -0,0 +1,313 @@
+use alloy::transports::http::reqwest;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tracing::warn;
+
+use crate::metrics;
+use crate::sync::LockExt;
+
+static ALERTER: OnceLock<Alerter> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    InvalidClaim,
+    ChallengeFailed,
+    LowBalance,
+    SyncStalled,
+    DeadLetter,
+    ClaimFailed,
+    RelayMismatch,
+}
+
+impl AlertKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            AlertKind::InvalidClaim => "invalid_claim",
+            AlertKind::ChallengeFailed => "challenge_failed",
+            AlertKind::LowBalance => "low_balance",
+            AlertKind::SyncStalled => "sync_stalled",
+            AlertKind::DeadLetter => "dead_letter",
+            AlertKind::ClaimFailed => "claim_failed",
+            AlertKind::RelayMismatch => "relay_mismatch",
+        }
+    }
+
+    /// Critical alerts need someone to act before a claim settles.
+    pub fn severity(self) -> &'static str {
+        match self {
+            AlertKind::InvalidClaim | AlertKind::ChallengeFailed | AlertKind::LowBalance | AlertKind::RelayMismatch => "critical",
+            AlertKind::SyncStalled | AlertKind::DeadLetter | AlertKind::ClaimFailed => "warning",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub severity: &'static str,
+    pub route: String,
+    /// What the alert is about within the route (an epoch, a chain), so the
+    /// same problem is deduplicated but a new epoch still pages.
+    pub subject: String,
+    pub message: String,
+    pub timestamp: u64,
+}
+
+impl Alert {
+    pub fn new(kind: AlertKind, route: &str, subject: impl ToString, message: impl ToString) -> Self {
+        Self {
+            kind,
+            severity: kind.severity(),
+            route: route.to_string(),
+            subject: subject.to_string(),
+            message: message.to_string(),
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        }
+    }
+
+    fn dedup_key(&self) -> String {
+        format!("{}/{}/{}", self.kind.name(), self.route, self.subject)
+    }
+
+    fn summary(&self) -> String {
+        format!("[{}] {} {}: {}", self.severity, self.route, self.kind.name(), self.message)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+    /// POSTs the alert as JSON.
+    Webhook { url: String },
+    /// Prints the alert as one JSON line on stdout.
+    Stdout,
+    /// Appends the alert as one JSON line to `path`.
+    File { path: PathBuf },
+    /// Mails the alert through an SMTP relay that accepts it without auth,
+    /// typically a local MTA.
+    Smtp { relay: String, from: String, to: Vec<String> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertConfig {
+    pub sinks: Vec<SinkConfig>,
+    /// Identical alerts within this window are sent once.
+    pub dedup_secs: u64,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self { sinks: Vec::new(), dedup_secs: 60 * 60 }
+    }
+}
+
+type SinkResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn send(&self, alert: &Alert) -> SinkResult;
+}
+
+pub struct WebhookSink {
+    url: String,
+    client: reqwest::Client,
+}
+
+#[async_trait]
+impl AlertSink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn send(&self, alert: &Alert) -> SinkResult {
+        self.client.post(&self.url)
+            .header("content-type", "application/json")
+            .body(serde_json::to_string(alert)?)
+            .send().await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+pub struct StdoutSink;
+
+#[async_trait]
+impl AlertSink for StdoutSink {
+    fn name(&self) -> &'static str {
+        "stdout"
+    }
+
+    async fn send(&self, alert: &Alert) -> SinkResult {
+        println!("{}", serde_json::to_string(alert)?);
+        Ok(())
+    }
+}
+
+pub struct FileSink {
+    path: PathBuf,
+}
+
+#[async_trait]
+impl AlertSink for FileSink {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn send(&self, alert: &Alert) -> SinkResult {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(&self.path).await?;
+        file.write_all(format!("{}\n", serde_json::to_string(alert)?).as_bytes()).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+pub struct SmtpSink {
+    relay: String,
+    from: String,
+    to: Vec<String>,
+}
+
+/// Reads one (possibly multi-line) SMTP reply and checks its status class.
+async fn smtp_reply(reader: &mut BufReader<TcpStream>, expect: char) -> SinkResult {
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Err("SMTP relay closed the connection".into());
+        }
+        if line.len() < 4 || !line.starts_with(expect) {
+            return Err(format!("unexpected SMTP reply: {}", line.trim_end()).into());
+        }
+        if line.as_bytes()[3] == b' ' {
+            return Ok(());
+        }
+    }
+}
+
+async fn smtp_command(reader: &mut BufReader<TcpStream>, command: &str, expect: char) -> SinkResult {
+    reader.get_mut().write_all(format!("{}\r\n", command).as_bytes()).await?;
+    smtp_reply(reader, expect).await
+}
+
+#[async_trait]
+impl AlertSink for SmtpSink {
+    fn name(&self) -> &'static str {
+        "smtp"
+    }
+
+    async fn send(&self, alert: &Alert) -> SinkResult {
+        let mut reader = BufReader::new(TcpStream::connect(&self.relay).await?);
+        smtp_reply(&mut reader, '2').await?;
+        smtp_command(&mut reader, "HELO vea-validator", '2').await?;
+        smtp_command(&mut reader, &format!("MAIL FROM:<{}>", self.from), '2').await?;
+        for to in &self.to {
+            smtp_command(&mut reader, &format!("RCPT TO:<{}>", to), '2').await?;
+        }
+        smtp_command(&mut reader, "DATA", '3').await?;
+
+        let body = serde_json::to_string_pretty(alert)?.replace("\n.", "\n..");
+        let message = format!(
+            "From: <{}>\r\nTo: {}\r\nSubject: {}\r\nContent-Type: application/json\r\n\r\n{}\r\n.",
+            self.from,
+            self.to.iter().map(|t| format!("<{}>", t)).collect::<Vec<_>>().join(", "),
+            alert.summary(),
+            body.replace('\n', "\r\n"),
+        );
+        smtp_command(&mut reader, &message, '2').await?;
+        smtp_command(&mut reader, "QUIT", '2').await
+    }
+}
+
+impl SinkConfig {
+    pub fn build(&self) -> Arc<dyn AlertSink> {
+        match self {
+            SinkConfig::Webhook { url } => Arc::new(WebhookSink { url: url.clone(), client: reqwest::Client::new() }),
+            SinkConfig::Stdout => Arc::new(StdoutSink),
+            SinkConfig::File { path } => Arc::new(FileSink { path: path.clone() }),
+            SinkConfig::Smtp { relay, from, to } => Arc::new(SmtpSink { relay: relay.clone(), from: from.clone(), to: to.clone() }),
+        }
+    }
+}
+
+/// Fans alerts out to every sink, sending each distinct alert at most once
+/// per `dedup_secs`. A failing sink is logged and does not hold up the others.
+pub struct Alerter {
+    sinks: Vec<Arc<dyn AlertSink>>,
+    dedup_secs: u64,
+    last_sent: Mutex<HashMap<String, u64>>,
+}
+
+impl Alerter {
+    pub fn new(config: &AlertConfig) -> Self {
+        Self {
+            sinks: config.sinks.iter().map(SinkConfig::build).collect(),
+            dedup_secs: config.dedup_secs,
+            last_sent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_sink(mut self, sink: Arc<dyn AlertSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    fn admit(&self, alert: &Alert) -> bool {
+        let mut last_sent = self.last_sent.locked();
+        last_sent.retain(|_, sent| alert.timestamp < *sent + self.dedup_secs);
+        if last_sent.contains_key(&alert.dedup_key()) {
+            return false;
+        }
+        last_sent.insert(alert.dedup_key(), alert.timestamp);
+        true
+    }
+
+    /// Delivers `alert` unless an identical one went out within the window.
+    /// Returns whether it was delivered.
+    pub async fn send(&self, alert: &Alert) -> bool {
+        if !self.admit(alert) {
+            metrics::record_alert(alert.kind.name(), "suppressed");
+            return false;
+        }
+        metrics::record_alert(alert.kind.name(), "sent");
+        for sink in &self.sinks {
+            if let Err(e) = sink.send(alert).await {
+                warn!(sink = sink.name(), kind = alert.kind.name(), error = %e, "Failed to deliver alert");
+            }
+        }
+        true
+    }
+}
+
+/// Installs the process-wide alerter used by `fire` and `notify`.
+pub fn init(alerter: Alerter) {
+    if ALERTER.set(alerter).is_err() {
+        warn!("Alerter already initialized");
+    }
+}
+
+/// Delivers in the background, for callers that must not wait on a sink.
+pub fn fire(alert: Alert) {
+    let Some(alerter) = ALERTER.get() else { return };
+    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+        handle.spawn(async move { alerter.send(&alert).await });
+    }
+}
+
+/// Delivers before returning, for alerts raised right before exiting.
+pub async fn notify(alert: Alert) {
+    if let Some(alerter) = ALERTER.get() {
+        alerter.send(&alert).await;
+    }
+}
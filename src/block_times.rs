@@ -0,0 +1,117 @@
+use alloy::network::Ethereum;
+use alloy::providers::{DynProvider, Provider};
+use alloy::rpc::types::Log;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::metrics;
+use crate::sync::LockExt;
+use crate::tasks::TaskError;
+
+const MAX_CACHED_BLOCKS: usize = 4096;
+
+#[derive(Default)]
+struct Cache {
+    timestamps: HashMap<u64, u64>,
+    order: VecDeque<u64>,
+}
+
+/// Block timestamps for one chain, remembered for the most recent
+/// `MAX_CACHED_BLOCKS` blocks looked up. Shared by every route on the chain.
+pub struct BlockTimes {
+    chain: String,
+    provider: DynProvider<Ethereum>,
+    avg_block_millis: u32,
+    cache: Mutex<Cache>,
+}
+
+impl BlockTimes {
+    pub fn new(chain: &str, provider: DynProvider<Ethereum>, avg_block_millis: u32) -> Self {
+        Self { chain: chain.to_string(), provider, avg_block_millis, cache: Mutex::new(Cache::default()) }
+    }
+
+    pub async fn timestamp(&self, block: u64) -> Result<u64, TaskError> {
+        if let Some(ts) = self.cache.locked().timestamps.get(&block) {
+            metrics::record_block_time_lookup(&self.chain, "hit");
+            return Ok(*ts);
+        }
+        metrics::record_block_time_lookup(&self.chain, "miss");
+        let ts = self.provider.get_block_by_number(block.into()).await?
+            .ok_or_else(|| TaskError::RpcUnavailable(format!("{} block {} not found", self.chain, block)))?
+            .header.timestamp;
+        self.remember(block, ts);
+        Ok(ts)
+    }
+
+    fn remember(&self, block: u64, ts: u64) {
+        let mut cache = self.cache.locked();
+        if cache.timestamps.insert(block, ts).is_none() {
+            cache.order.push_back(block);
+            if cache.order.len() > MAX_CACHED_BLOCKS {
+                let oldest = cache.order.pop_front().unwrap();
+                cache.timestamps.remove(&oldest);
+            }
+        }
+    }
+
+    pub async fn log_timestamp(&self, log: &Log) -> Result<u64, TaskError> {
+        let block = log.block_number
+            .ok_or_else(|| TaskError::RpcUnavailable(format!("{} log has no block number", self.chain)))?;
+        match log.block_timestamp {
+            Some(ts) => {
+                self.remember(block, ts);
+                Ok(ts)
+            }
+            None => self.timestamp(block).await,
+        }
+    }
+
+    /// First block at or after `target_ts`, or the latest block if none is.
+    /// Guesses from `avg_block_millis` and interpolates between the blocks
+    /// seen so far, bisecting whenever a guess fails to halve the range.
+    pub async fn find_block(&self, target_ts: u64) -> Result<u64, TaskError> {
+        let latest = self.provider.get_block_number().await?;
+        let latest_ts = self.timestamp(latest).await?;
+        if target_ts >= latest_ts {
+            return Ok(latest);
+        }
+
+        // Step back from the head until a block is before the target, widening
+        // the step if blocks came faster than average.
+        let (mut hi, mut hi_ts) = (latest, latest_ts);
+        let mut widen = 1;
+        let (mut lo, mut lo_ts) = loop {
+            let estimate = ((hi_ts - target_ts) * 1000 / self.avg_block_millis.max(1) as u64).max(1);
+            let guess = hi.saturating_sub(estimate.saturating_mul(widen));
+            let ts = self.timestamp(guess).await?;
+            if ts < target_ts {
+                break (guess, ts);
+            }
+            if guess == 0 {
+                return Ok(0);
+            }
+            (hi, hi_ts) = (guess, ts);
+            widen *= 2;
+        };
+
+        // Invariant: lo is before the target, hi is at or after it.
+        let mut bisect = false;
+        while hi - lo > 1 {
+            let mid = if bisect {
+                lo + (hi - lo) / 2
+            } else {
+                let offset = (hi - lo) as u128 * (target_ts - lo_ts) as u128 / (hi_ts - lo_ts) as u128;
+                (lo + offset as u64).clamp(lo + 1, hi - 1)
+            };
+            let ts = self.timestamp(mid).await?;
+            let before = hi - lo;
+            if ts < target_ts {
+                (lo, lo_ts) = (mid, ts);
+            } else {
+                (hi, hi_ts) = (mid, ts);
+            }
+            bisect = !bisect && (hi - lo) * 2 > before;
+        }
+        Ok(hi)
+    }
+}
@@ -0,0 +1,320 @@
+use alloy::primitives::U256;
+use alloy::providers::Provider;
+use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::{ConfigFile, RouteConfig, ValidatorConfig};
+use crate::startup::{check_rpc_health, check_signer, inspect_balances, load_route_settings};
+use crate::tasks::nonce::{journal_path, read_journal};
+use crate::tasks::storage::open_route_stores;
+use crate::tasks::TaskKind;
+
+type CliResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+#[derive(Parser)]
+#[command(name = "vea-validator", about = "VEA bridge validator")]
+pub struct Cli {
+    /// Directory holding the per-route task and claim state
+    #[arg(long, global = true, default_value = "data")]
+    pub data_dir: PathBuf,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run the validator (default)
+    Run,
+    /// Show sync progress and pending tasks per route, plus chain heads and balances
+    Status {
+        /// Only read the data directory, skip RPC calls
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Inspect or edit the task queue (stop the validator or use the admin API while it runs)
+    Tasks {
+        #[command(subcommand)]
+        action: TasksCommand,
+    },
+    /// Inspect stored claims
+    Claims {
+        #[command(subcommand)]
+        action: ClaimsCommand,
+    },
+    /// Reset indexing_since and the block cursors to re-index from a timestamp
+    Resync {
+        /// Unix timestamp to index from; must be within the route's sync lookback
+        #[arg(long)]
+        from: u64,
+        #[arg(long)]
+        route: Option<String>,
+    },
+    /// Run the startup checks (RPC health, signer, balances, allowances, route settings) without sending anything and exit
+    Check,
+}
+
+#[derive(Subcommand)]
+pub enum TasksCommand {
+    List {
+        #[arg(long)]
+        route: Option<String>,
+    },
+    Drop {
+        #[arg(long)]
+        route: String,
+        epoch: u64,
+        kind: String,
+    },
+    Reschedule {
+        #[arg(long)]
+        route: String,
+        epoch: u64,
+        kind: String,
+        execute_after: u64,
+    },
+    /// Move a dead-lettered task back into the queue with a fresh retry budget
+    Requeue {
+        #[arg(long)]
+        route: String,
+        epoch: u64,
+        kind: String,
+        #[arg(long, default_value_t = 0)]
+        execute_after: u64,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ClaimsCommand {
+    Show {
+        epoch: u64,
+        #[arg(long)]
+        route: Option<String>,
+    },
+}
+
+fn select_routes<'a>(file: &'a ConfigFile, name: Option<&str>) -> Result<Vec<&'a RouteConfig>, Box<dyn std::error::Error + Send + Sync>> {
+    match name {
+        None => Ok(file.routes.iter().collect()),
+        Some(name) => {
+            let route = file.routes.iter().find(|r| r.name == name)
+                .ok_or_else(|| format!("Unknown route {}", name))?;
+            Ok(vec![route])
+        }
+    }
+}
+
+fn check_kind(kind: &str) -> CliResult {
+    if !TaskKind::NAMES.contains(&kind) {
+        return Err(format!("Unknown task kind {} (expected one of {})", kind, TaskKind::NAMES.join(", ")).into());
+    }
+    Ok(())
+}
+
+/// Executes every subcommand except `run`.
+pub async fn execute(command: Command, data_dir: PathBuf) -> CliResult {
+    match command {
+        Command::Run => unreachable!("run is handled by main"),
+        Command::Status { offline } => status(&data_dir, offline).await,
+        Command::Tasks { action } => tasks(&data_dir, action),
+        Command::Claims { action: ClaimsCommand::Show { epoch, route } } => claims_show(&data_dir, epoch, route.as_deref()),
+        Command::Resync { from, route } => resync(&data_dir, from, route.as_deref()).await,
+        Command::Check => check().await,
+    }
+}
+
+async fn status(data_dir: &Path, offline: bool) -> CliResult {
+    let file = ConfigFile::from_env()?;
+    let online = if offline {
+        None
+    } else {
+        let c = ValidatorConfig::from_env()?;
+        let routes = c.build_routes();
+        Some((c, routes))
+    };
+
+    for route_cfg in &file.routes {
+        let (task_store, claim_store) = open_route_stores(file.storage, data_dir, &route_cfg.name);
+        let state = task_store.load();
+        println!("{}", route_cfg.name);
+        println!("  on_sync: {}", state.on_sync);
+        println!("  indexing_since: {:?}", state.indexing_since);
+        println!("  inbox_last_block: {:?}", state.inbox_last_block);
+        println!("  outbox_last_block: {:?}", state.outbox_last_block);
+        println!("  claims: {}", claim_store.all().len());
+        println!("  pending tasks: {}", state.tasks.len());
+        println!("  dead letters: {}", state.dead_letters.len());
+        for missed in &state.missed_deadlines {
+            println!("  missed deadline: epoch={} kind={} deadline={} detected_at={}", missed.epoch, missed.kind, missed.deadline, missed.detected_at);
+        }
+        for kind in TaskKind::NAMES {
+            let count = state.tasks.iter().filter(|t| t.kind.name() == kind).count();
+            if count > 0 {
+                println!("    {}: {}", kind, count);
+            }
+        }
+
+        let Some((c, routes)) = &online else { continue };
+        let route = routes.iter().find(|r| r.name == route_cfg.name).expect("Route not built");
+        let wallet = c.address();
+        let inbox_head = route.inbox_provider.get_block_number().await?;
+        let outbox_head = route.outbox_provider.get_block_number().await?;
+        println!("  inbox head: {} ({})", inbox_head, route.inbox_chain_name);
+        println!("  outbox head: {} ({})", outbox_head, route.outbox_chain_name);
+        let balance = route.adapter.deposit_balance(route, wallet).await?;
+        println!("  balance: {} wei {}", balance, route.adapter.deposit_currency(route));
+        if let Some(native) = route.adapter.gas_balance(route, wallet).await? {
+            println!("  balance: {} wei {}", native, route.outbox_native_currency);
+        }
+    }
+
+    let mut chain_ids: Vec<_> = file.chains.keys().copied().collect();
+    chain_ids.sort();
+    for chain_id in chain_ids {
+        let pending = read_journal(&journal_path(data_dir, chain_id));
+        if pending.is_empty() {
+            continue;
+        }
+        println!("{} pending transactions", file.chains[&chain_id].name);
+        for tx in pending {
+            println!("  nonce={} action={} sent_block={} replacements={} tx_hash={}",
+                tx.nonce, tx.action, tx.sent_block, tx.hashes.len() - 1, tx.hashes[tx.hashes.len() - 1]);
+        }
+    }
+    Ok(())
+}
+
+fn tasks(data_dir: &Path, action: TasksCommand) -> CliResult {
+    let file = ConfigFile::from_env()?;
+    match action {
+        TasksCommand::List { route } => {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            for route_cfg in select_routes(&file, route.as_deref())? {
+                let (task_store, _) = open_route_stores(file.storage, data_dir, &route_cfg.name);
+                let state = task_store.load();
+                let mut tasks = state.tasks;
+                tasks.sort_by_key(|t| (t.due_at(), t.epoch));
+                println!("{} ({} tasks)", route_cfg.name, tasks.len());
+                for task in tasks {
+                    let due = if task.due_at() <= now { "due".to_string() } else { format!("in {}s", task.due_at() - now) };
+                    let deadline = task.deadline.map(|d| format!(" deadline={}", d)).unwrap_or_default();
+                    println!("  epoch={} kind={} execute_after={} ({}){}", task.epoch, task.kind.name(), task.execute_after, due, deadline);
+                    if let Some(error) = &task.last_error {
+                        println!("    attempts={} last_error={}", task.attempts, error);
+                    }
+                }
+                if !state.dead_letters.is_empty() {
+                    println!("  dead letters ({}):", state.dead_letters.len());
+                    for task in state.dead_letters {
+                        println!("    epoch={} kind={} attempts={} last_error={}",
+                            task.epoch, task.kind.name(), task.attempts, task.last_error.unwrap_or_default());
+                    }
+                }
+            }
+        }
+        TasksCommand::Drop { route, epoch, kind } => {
+            check_kind(&kind)?;
+            let route_cfg = select_routes(&file, Some(&route))?[0];
+            let (task_store, _) = open_route_stores(file.storage, data_dir, &route_cfg.name);
+            let task = task_store.find_task(epoch, &kind)
+                .ok_or_else(|| format!("No {} task for epoch {}", kind, epoch))?;
+            task_store.remove_task(&task);
+            println!("Dropped {} task for epoch {}", kind, epoch);
+        }
+        TasksCommand::Reschedule { route, epoch, kind, execute_after } => {
+            check_kind(&kind)?;
+            let route_cfg = select_routes(&file, Some(&route))?[0];
+            let (task_store, _) = open_route_stores(file.storage, data_dir, &route_cfg.name);
+            let task = task_store.find_task(epoch, &kind)
+                .ok_or_else(|| format!("No {} task for epoch {}", kind, epoch))?;
+            task_store.reschedule_task(&task, execute_after);
+            println!("Rescheduled {} task for epoch {} to {}", kind, epoch, execute_after);
+        }
+        TasksCommand::Requeue { route, epoch, kind, execute_after } => {
+            check_kind(&kind)?;
+            let route_cfg = select_routes(&file, Some(&route))?[0];
+            let (task_store, _) = open_route_stores(file.storage, data_dir, &route_cfg.name);
+            let task = task_store.find_dead_letter(epoch, &kind)
+                .ok_or_else(|| format!("No dead-lettered {} task for epoch {}", kind, epoch))?;
+            task_store.requeue_dead_letter(&task, execute_after);
+            println!("Requeued {} task for epoch {}", kind, epoch);
+        }
+    }
+    Ok(())
+}
+
+fn claims_show(data_dir: &Path, epoch: u64, route: Option<&str>) -> CliResult {
+    let file = ConfigFile::from_env()?;
+    for route_cfg in select_routes(&file, route)? {
+        let (_, claim_store) = open_route_stores(file.storage, data_dir, &route_cfg.name);
+        if claim_store.exists(epoch) {
+            println!("{}: {}", route_cfg.name, serde_json::to_string_pretty(&claim_store.get(epoch))?);
+        } else {
+            println!("{}: no claim for epoch {}", route_cfg.name, epoch);
+        }
+    }
+    Ok(())
+}
+
+async fn resync(data_dir: &Path, from: u64, route: Option<&str>) -> CliResult {
+    let c = ValidatorConfig::from_env()?;
+    let routes = c.build_routes();
+    let file = ConfigFile::from_env()?;
+    let selected: Vec<_> = select_routes(&file, route)?.into_iter()
+        .map(|route_cfg| routes.iter().find(|r| r.name == route_cfg.name).expect("Route not built"))
+        .collect();
+
+    // The indexer re-initializes cursors older than the sync lookback on
+    // startup, so an earlier `from` would be silently replaced.
+    let arb_outbox_provider = c.build_provider(c.arb_outbox_chain_id);
+    for route in &selected {
+        let settings = load_route_settings(route, c.arb_outbox, &arb_outbox_provider).await;
+        let now = route.inbox_blocks.timestamp(route.inbox_provider.get_block_number().await?).await?;
+        let earliest = now.saturating_sub(settings.sync_lookback_secs);
+        if from < earliest || from > now {
+            return Err(format!(
+                "{}: --from must be between {} and {} (sync lookback {}s)",
+                route.name, earliest, now, settings.sync_lookback_secs,
+            ).into());
+        }
+    }
+
+    for route in selected {
+        let inbox_start = route.inbox_blocks.find_block(from).await?;
+        let outbox_start = route.outbox_blocks.find_block(from).await?;
+        let (task_store, _) = open_route_stores(c.storage, data_dir, &route.name);
+        task_store.initialize_sync(from, inbox_start, outbox_start);
+        task_store.set_on_sync(false);
+        println!("{}: indexing from {} (inbox block {}, outbox block {})", route.name, from, inbox_start, outbox_start);
+    }
+    Ok(())
+}
+
+async fn check() -> CliResult {
+    let c = ValidatorConfig::from_env()?;
+    let routes = c.build_routes();
+    check_rpc_health(&routes).await?;
+    check_signer(&c).await?;
+    let shortfalls = inspect_balances(c.address(), &routes).await?;
+    for (route, msg) in &shortfalls {
+        println!("{}: {}", route.name, msg);
+    }
+    for route in &routes {
+        match route.adapter.deposit_allowance(route, c.address()).await? {
+            Some(allowance) if allowance == U256::ZERO => {
+                println!("{}: outbox has no {} allowance yet; `run` will approve it", route.name, route.adapter.deposit_currency(route));
+            }
+            Some(allowance) => println!("{}: outbox {} allowance {}", route.name, route.adapter.deposit_currency(route), allowance),
+            None => {}
+        }
+    }
+    let arb_outbox_provider = c.build_provider(c.arb_outbox_chain_id);
+    for route in &routes {
+        load_route_settings(route, c.arb_outbox, &arb_outbox_provider).await;
+    }
+    if !shortfalls.is_empty() {
+        return Err(format!("{} balance check(s) failed", shortfalls.len()).into());
+    }
+    println!("All startup checks passed");
+    Ok(())
+}
@@ -4,17 +4,444 @@ use alloy::providers::{ProviderBuilder, DynProvider};
 use alloy::rpc::client::RpcClient;
 use alloy::transports::http::Http;
 use alloy::transports::layers::FallbackLayer;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
 use std::num::NonZeroUsize;
+use std::path::Path;
 use std::str::FromStr;
 use std::collections::HashMap;
+use std::sync::Arc;
 use tower::ServiceBuilder;
 
-#[derive(Debug, Clone)]
+use crate::adapters::RouteAdapter;
+use crate::alerts::{AlertConfig, SinkConfig};
+use crate::block_times::BlockTimes;
+use crate::log_chunks::{LogChunkConfig, LogChunker};
+use crate::signer::SignerConfig;
+use crate::supervisor::SupervisorConfig;
+use crate::tasks::claim_policy::ClaimPolicy;
+use crate::tasks::deadline::DeadlineConfig;
+use crate::tasks::dispatcher::DispatcherConfig;
+use crate::tasks::fees::{FeePolicy, FeeTier};
+use crate::tasks::nonce::{NonceManager, TxConfig};
+use crate::tasks::recorder::TxRecorder;
+use crate::tasks::retry::RetryConfig;
+use crate::tasks::TaskKind;
+use crate::tasks::storage::StorageKind;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChainInfo {
     pub name: String,
+    #[serde(default)]
     pub rpc_urls: Vec<String>,
+    #[serde(default)]
     pub deposit_token: Option<Address>,
     pub avg_block_millis: u32,
+    #[serde(default = "default_native_currency")]
+    pub native_currency: String,
+    #[serde(default)]
+    pub fees: FeePolicy,
+    #[serde(default)]
+    pub finality: Finality,
+    #[serde(default)]
+    pub log_chunks: LogChunkConfig,
+}
+
+/// Which blocks the indexer treats as final on a chain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Finality {
+    /// Blocks at least 15 minutes old.
+    #[default]
+    Buffer,
+    /// Up to the node's `safe` block.
+    Safe,
+    /// Up to the node's `finalized` block.
+    Finalized,
+}
+
+impl FromStr for Finality {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "buffer" => Ok(Finality::Buffer),
+            "safe" => Ok(Finality::Safe),
+            "finalized" => Ok(Finality::Finalized),
+            other => Err(format!("Invalid finality {} (expected buffer, safe or finalized)", other)),
+        }
+    }
+}
+
+fn default_native_currency() -> String {
+    "ETH".to_string()
+}
+
+/// Prefix or suffix of a chain's or route's env overrides, e.g. `ARB_TO_ETH`.
+fn env_key(name: &str) -> String {
+    name.to_uppercase().replace(['-', ' '], "_")
+}
+
+impl ChainInfo {
+    fn env_key(&self) -> String {
+        env_key(&self.name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteContracts {
+    ArbToEth,
+    ArbToGnosis,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteConfig {
+    pub name: String,
+    pub contracts: RouteContracts,
+    pub inbox_chain_id: u64,
+    #[serde(default)]
+    pub inbox_address: Address,
+    pub outbox_chain_id: u64,
+    #[serde(default)]
+    pub outbox_address: Address,
+    #[serde(default)]
+    pub deposit_token: Option<Address>,
+}
+
+impl RouteConfig {
+    fn env_key(&self) -> String {
+        env_key(&self.name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigFile {
+    pub chains: HashMap<u64, ChainInfo>,
+    pub routes: Vec<RouteConfig>,
+    #[serde(default)]
+    pub arb_outbox: Address,
+    pub arb_outbox_chain_id: u64,
+    #[serde(default)]
+    pub make_claims: Option<bool>,
+    #[serde(default)]
+    pub storage: StorageKind,
+    #[serde(default)]
+    pub metrics_addr: Option<String>,
+    #[serde(default)]
+    pub admin_addr: Option<String>,
+    #[serde(default)]
+    pub supervisor: SupervisorConfig,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    #[serde(default)]
+    pub deadlines: DeadlineConfig,
+    #[serde(default)]
+    pub dispatcher: DispatcherConfig,
+    #[serde(default)]
+    pub tx: TxConfig,
+    #[serde(default)]
+    pub claims: ClaimPolicy,
+    /// Run the full pipeline but record transactions instead of sending them.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Address to act as in dry-run mode when no key is given.
+    #[serde(default)]
+    pub validator_address: Option<Address>,
+    /// Also relay snapshots other validators sent, once their claim checks out.
+    #[serde(default)]
+    pub piggyback_relays: bool,
+    #[serde(default)]
+    pub alerts: AlertConfig,
+    #[serde(default)]
+    pub signer: SignerConfig,
+}
+
+impl ConfigFile {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+        let file = serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse config file {}: {}", path.display(), e))?;
+        Ok(file)
+    }
+
+    /// Reads the JSON file named by `VALIDATOR_CONFIG` (or the built-in
+    /// Arbitrum → Ethereum/Gnosis routes), then applies env overrides.
+    pub fn from_env() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut file = match std::env::var("VALIDATOR_CONFIG") {
+            Ok(path) => ConfigFile::load(path)?,
+            Err(_) => ConfigFile::builtin(),
+        };
+        file.apply_env()?;
+        Ok(file)
+    }
+
+    /// The Arbitrum → Ethereum and Arbitrum → Gnosis deployments, with every
+    /// address and RPC URL left for the environment to fill in.
+    pub fn builtin() -> Self {
+        let mut chains = HashMap::new();
+        chains.insert(42161, ChainInfo {
+            name: "Arbitrum".to_string(),
+            rpc_urls: Vec::new(),
+            deposit_token: None,
+            avg_block_millis: 250,
+            native_currency: "ETH".to_string(),
+            fees: FeePolicy::default(),
+            finality: Finality::Buffer,
+            log_chunks: LogChunkConfig::default(),
+        });
+        chains.insert(1, ChainInfo {
+            name: "Ethereum".to_string(),
+            rpc_urls: Vec::new(),
+            deposit_token: None,
+            avg_block_millis: 12000,
+            native_currency: "ETH".to_string(),
+            fees: FeePolicy::default(),
+            finality: Finality::Buffer,
+            log_chunks: LogChunkConfig::default(),
+        });
+        chains.insert(100, ChainInfo {
+            name: "Gnosis".to_string(),
+            rpc_urls: Vec::new(),
+            deposit_token: None,
+            avg_block_millis: 5000,
+            native_currency: "xDAI".to_string(),
+            fees: FeePolicy::default(),
+            finality: Finality::Buffer,
+            log_chunks: LogChunkConfig::default(),
+        });
+
+        Self {
+            chains,
+            routes: vec![
+                RouteConfig {
+                    name: "ARB_TO_ETH".to_string(),
+                    contracts: RouteContracts::ArbToEth,
+                    inbox_chain_id: 42161,
+                    inbox_address: Address::ZERO,
+                    outbox_chain_id: 1,
+                    outbox_address: Address::ZERO,
+                    deposit_token: None,
+                },
+                RouteConfig {
+                    name: "ARB_TO_GNOSIS".to_string(),
+                    contracts: RouteContracts::ArbToGnosis,
+                    inbox_chain_id: 42161,
+                    inbox_address: Address::ZERO,
+                    outbox_chain_id: 100,
+                    outbox_address: Address::ZERO,
+                    deposit_token: None,
+                },
+            ],
+            arb_outbox: Address::ZERO,
+            arb_outbox_chain_id: 1,
+            make_claims: None,
+            storage: StorageKind::Json,
+            metrics_addr: None,
+            admin_addr: None,
+            supervisor: SupervisorConfig::default(),
+            retry: RetryConfig::default(),
+            deadlines: DeadlineConfig::default(),
+            dispatcher: DispatcherConfig::default(),
+            tx: TxConfig::default(),
+            claims: ClaimPolicy::default(),
+            dry_run: false,
+            validator_address: None,
+            piggyback_relays: false,
+            alerts: AlertConfig::default(),
+            signer: SignerConfig::default(),
+        }
+    }
+
+    /// Overrides individual fields from the environment:
+    /// `<CHAIN>_RPC_URL`, `WETH_<CHAIN>`, `<CHAIN>_FINALITY`, `VEA_INBOX_<ROUTE>`, `VEA_OUTBOX_<ROUTE>`,
+    /// `ARB_OUTBOX`, `MAKE_CLAIMS`, `STORAGE_BACKEND`, `METRICS_ADDR`, `ADMIN_ADDR`,
+    /// `DRY_RUN`, `VALIDATOR_ADDRESS`, `PIGGYBACK_RELAYS`, `KEYSTORE_PATH` (with `KEYSTORE_PASSWORD_FILE`)
+    /// and `REMOTE_SIGNER_URL` (with `REMOTE_SIGNER_ADDRESS`).
+    pub fn apply_env(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        for chain in self.chains.values_mut() {
+            let key = chain.env_key();
+            if let Ok(urls) = std::env::var(format!("{}_RPC_URL", key)) {
+                chain.rpc_urls = parse_rpc_urls(&urls);
+            }
+            if let Some(token) = env_address(&format!("WETH_{}", key))? {
+                chain.deposit_token = Some(token);
+            }
+            if let Ok(finality) = std::env::var(format!("{}_FINALITY", key)) {
+                chain.finality = finality.parse()?;
+            }
+        }
+
+        for route in self.routes.iter_mut() {
+            let key = route.env_key();
+            if let Some(inbox) = env_address(&format!("VEA_INBOX_{}", key))? {
+                route.inbox_address = inbox;
+            }
+            if let Some(outbox) = env_address(&format!("VEA_OUTBOX_{}", key))? {
+                route.outbox_address = outbox;
+            }
+        }
+
+        if let Some(arb_outbox) = env_address("ARB_OUTBOX")? {
+            self.arb_outbox = arb_outbox;
+        }
+        if let Ok(v) = std::env::var("MAKE_CLAIMS") {
+            self.make_claims = Some(v.to_lowercase() == "true" || v == "1");
+        }
+        if let Ok(v) = std::env::var("STORAGE_BACKEND") {
+            self.storage = match v.to_lowercase().as_str() {
+                "json" => StorageKind::Json,
+                "sqlite" => StorageKind::Sqlite,
+                other => return Err(format!("Invalid STORAGE_BACKEND: {} (expected json or sqlite)", other).into()),
+            };
+        }
+        if let Ok(v) = std::env::var("METRICS_ADDR") {
+            self.metrics_addr = Some(v);
+        }
+        if let Ok(v) = std::env::var("ADMIN_ADDR") {
+            self.admin_addr = Some(v);
+        }
+        if let Ok(v) = std::env::var("DRY_RUN") {
+            self.dry_run = v.to_lowercase() == "true" || v == "1";
+        }
+        if let Some(address) = env_address("VALIDATOR_ADDRESS")? {
+            self.validator_address = Some(address);
+        }
+        if let Ok(v) = std::env::var("PIGGYBACK_RELAYS") {
+            self.piggyback_relays = v.to_lowercase() == "true" || v == "1";
+        }
+        if let Ok(path) = std::env::var("KEYSTORE_PATH") {
+            let password_file = std::env::var("KEYSTORE_PASSWORD_FILE")
+                .unwrap_or_else(|_| "/run/secrets/keystore_password".to_string());
+            self.signer = SignerConfig::Keystore { path: path.into(), password_file: password_file.into() };
+        }
+        if let Ok(url) = std::env::var("REMOTE_SIGNER_URL") {
+            let address = env_address("REMOTE_SIGNER_ADDRESS")?.ok_or("REMOTE_SIGNER_ADDRESS must be set")?;
+            self.signer = SignerConfig::Remote { url, address };
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self.routes.is_empty() {
+            return Err("No routes configured".into());
+        }
+        for route in &self.routes {
+            let key = route.env_key();
+            for chain_id in [route.inbox_chain_id, route.outbox_chain_id] {
+                let chain = self.chains.get(&chain_id)
+                    .ok_or_else(|| format!("Route {} references unknown chain {}", route.name, chain_id))?;
+                if chain.rpc_urls.is_empty() {
+                    return Err(format!("{}_RPC_URL must be set", chain.env_key()).into());
+                }
+            }
+            if route.inbox_address == Address::ZERO {
+                return Err(format!("VEA_INBOX_{} must be set", key).into());
+            }
+            if route.outbox_address == Address::ZERO {
+                return Err(format!("VEA_OUTBOX_{} must be set", key).into());
+            }
+            if route.contracts == RouteContracts::ArbToGnosis && self.deposit_token(route).is_none() {
+                let chain = &self.chains[&route.outbox_chain_id];
+                return Err(format!("WETH_{} must be set", chain.env_key()).into());
+            }
+        }
+        if !self.chains.contains_key(&self.arb_outbox_chain_id) {
+            return Err(format!("Arbitrum outbox chain {} not configured", self.arb_outbox_chain_id).into());
+        }
+        if self.arb_outbox == Address::ZERO {
+            return Err("ARB_OUTBOX must be set".into());
+        }
+        if self.make_claims.is_none() {
+            return Err("MAKE_CLAIMS must be set".into());
+        }
+        if let Some(addr) = &self.metrics_addr {
+            addr.parse::<SocketAddr>().map_err(|e| format!("Invalid METRICS_ADDR {}: {}", addr, e))?;
+        }
+        if let Some(addr) = &self.admin_addr {
+            addr.parse::<SocketAddr>().map_err(|e| format!("Invalid ADMIN_ADDR {}: {}", addr, e))?;
+        }
+        if self.validator_address.is_some() && !self.dry_run {
+            return Err("VALIDATOR_ADDRESS is only used with DRY_RUN".into());
+        }
+        if self.dispatcher.max_concurrent_tasks == 0 {
+            return Err("dispatcher.max_concurrent_tasks must be at least 1".into());
+        }
+        if self.deadlines.critical_secs > self.deadlines.elevated_secs {
+            return Err("deadlines.critical_secs must not exceed deadlines.elevated_secs".into());
+        }
+        if self.tx.replace_after_blocks == 0 {
+            return Err("tx.replace_after_blocks must be at least 1".into());
+        }
+        if self.tx.bump_percent < 10 {
+            return Err("tx.bump_percent must be at least 10".into());
+        }
+        if self.tx.fee_cap_percent < 100 {
+            return Err("tx.fee_cap_percent must be at least 100".into());
+        }
+        for chain in self.chains.values() {
+            let chunks = &chain.log_chunks;
+            if chunks.min_blocks == 0 || chunks.min_blocks > chunks.max_blocks {
+                return Err(format!("{} log_chunks needs 1 <= min_blocks <= max_blocks", chain.name).into());
+            }
+            for tier in [FeeTier::Low, FeeTier::Normal, FeeTier::High] {
+                let policy = chain.fees.tier(tier);
+                if policy.fee_percent == 0 {
+                    return Err(format!("{} fees.{}.fee_percent must be positive", chain.name, tier.name()).into());
+                }
+                if let (Some(max_fee), Some(priority_fee)) = (policy.max_fee_per_gas, policy.max_priority_fee_per_gas)
+                    && priority_fee > max_fee
+                {
+                    return Err(format!("{} fees.{}.max_priority_fee_per_gas exceeds max_fee_per_gas", chain.name, tier.name()).into());
+                }
+            }
+        }
+        for sink in &self.alerts.sinks {
+            match sink {
+                SinkConfig::Webhook { url } if !url.starts_with("http://") && !url.starts_with("https://") => {
+                    return Err(format!("Invalid alert webhook URL: {}", url).into());
+                }
+                SinkConfig::Smtp { to, .. } if to.is_empty() => {
+                    return Err("SMTP alert sink needs at least one recipient".into());
+                }
+                _ => {}
+            }
+        }
+        if let SignerConfig::Remote { url, .. } = &self.signer
+            && !url.starts_with("http://") && !url.starts_with("https://")
+        {
+            return Err(format!("Invalid remote signer URL: {}", url).into());
+        }
+        for kind in self.retry.kinds.keys() {
+            if !TaskKind::NAMES.contains(&kind.as_str()) {
+                return Err(format!("Unknown task kind {} in retry config", kind).into());
+            }
+        }
+        Ok(())
+    }
+
+    fn deposit_token(&self, route: &RouteConfig) -> Option<Address> {
+        route.deposit_token.or_else(|| self.chains.get(&route.outbox_chain_id)?.deposit_token)
+    }
+}
+
+fn parse_rpc_urls(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn env_address(env_var: &str) -> Result<Option<Address>, Box<dyn std::error::Error + Send + Sync>> {
+    match std::env::var(env_var) {
+        Ok(v) if !v.trim().is_empty() => Ok(Some(
+            Address::from_str(v.trim()).map_err(|e| format!("Invalid {}: {}", env_var, e))?
+        )),
+        _ => Ok(None),
+    }
 }
 
 #[derive(Clone)]
@@ -23,6 +450,9 @@ pub struct RouteSettings {
     pub start_verification_delay: u64,
     pub min_challenge_period: u64,
     pub sync_lookback_secs: u64,
+    /// Unpadded time from a claim until `verifySnapshot` can first succeed:
+    /// `sequencerDelayLimit + epochPeriod + minChallengePeriod`.
+    pub challenge_window_secs: u64,
 }
 
 impl RouteSettings {
@@ -32,38 +462,83 @@ impl RouteSettings {
             start_verification_delay: 86400 + 3600,
             min_challenge_period: 600,
             sync_lookback_secs: 7 * 24 * 3600 + 24 * 3600,
+            challenge_window_secs: 86400 + 600,
         }
     }
+
+    /// A challenge must land before the claim can be verified.
+    pub fn challenge_deadline(&self, timestamp_claimed: u64) -> u64 {
+        timestamp_claimed + self.challenge_window_secs
+    }
+
+    /// Until `verifySnapshot` lands anyone can still challenge the claim and
+    /// force the slow native-bridge path, so verification gets one more
+    /// challenge period before it counts as late.
+    pub fn verify_deadline(&self, verifiable_at: u64) -> u64 {
+        verifiable_at + self.min_challenge_period
+    }
 }
 
 #[derive(Clone)]
 pub struct Route {
-    pub name: &'static str,
+    pub name: String,
+    pub adapter: Arc<dyn RouteAdapter>,
     pub inbox_chain_id: u64,
+    pub inbox_chain_name: String,
     pub inbox_address: Address,
     pub inbox_provider: DynProvider<Ethereum>,
+    pub inbox_nonces: Arc<NonceManager>,
     pub inbox_avg_block_millis: u32,
+    pub inbox_fees: FeePolicy,
+    pub inbox_finality: Finality,
+    pub inbox_log_chunks: Arc<LogChunker>,
+    pub inbox_blocks: Arc<BlockTimes>,
     pub outbox_chain_id: u64,
+    pub outbox_chain_name: String,
+    pub outbox_native_currency: String,
     pub outbox_address: Address,
     pub outbox_provider: DynProvider<Ethereum>,
+    pub outbox_nonces: Arc<NonceManager>,
+    pub outbox_fees: FeePolicy,
+    pub outbox_finality: Finality,
+    pub outbox_log_chunks: Arc<LogChunker>,
+    pub outbox_blocks: Arc<BlockTimes>,
     pub weth_address: Option<Address>,
+    pub claim_policy: ClaimPolicy,
     pub settings: RouteSettings,
 }
 
 #[derive(Clone)]
 pub struct ValidatorConfig {
-    pub private_key: String,
     pub wallet: EthereumWallet,
+    pub signer: SignerConfig,
     pub chains: HashMap<u64, ChainInfo>,
-    pub inbox_arb_to_eth: Address,
-    pub outbox_arb_to_eth: Address,
-    pub inbox_arb_to_gnosis: Address,
-    pub outbox_arb_to_gnosis: Address,
+    pub routes: Vec<RouteConfig>,
     pub arb_outbox: Address,
+    pub arb_outbox_chain_id: u64,
     pub make_claims: bool,
+    pub storage: StorageKind,
+    pub metrics_addr: Option<SocketAddr>,
+    pub admin_addr: Option<SocketAddr>,
+    pub supervisor: SupervisorConfig,
+    pub retry: RetryConfig,
+    pub deadlines: DeadlineConfig,
+    pub dispatcher: DispatcherConfig,
+    pub tx: TxConfig,
+    pub claims: ClaimPolicy,
+    pub dry_run: bool,
+    /// Overrides the signer's address in dry-run mode.
+    pub watch_address: Option<Address>,
+    pub piggyback_relays: bool,
+    pub alerts: AlertConfig,
 }
 impl ValidatorConfig {
-    fn build_provider(&self, chain_id: u64) -> DynProvider<Ethereum> {
+    /// The address the validator acts as.
+    pub fn address(&self) -> Address {
+        self.watch_address.unwrap_or_else(|| self.wallet.default_signer().address())
+    }
+
+    pub fn build_provider(&self, chain_id: u64) -> DynProvider<Ethereum> {
         let chain = self.chains.get(&chain_id).expect("Chain not found");
         let urls = &chain.rpc_urls;
 
@@ -95,121 +570,96 @@ impl ValidatorConfig {
     }
 
     pub fn build_routes(&self) -> Vec<Route> {
-        let arb_provider = self.build_provider(42161);
-        let eth_provider = self.build_provider(1);
-        let gnosis_provider = self.build_provider(100);
-
-        vec![
-            Route {
-                name: "ARB_TO_ETH",
-                inbox_chain_id: 42161,
-                inbox_address: self.inbox_arb_to_eth,
-                inbox_provider: arb_provider.clone(),
-                inbox_avg_block_millis: 250,
-                outbox_chain_id: 1,
-                outbox_address: self.outbox_arb_to_eth,
-                outbox_provider: eth_provider.clone(),
-                weth_address: self.chains.get(&1).expect("Ethereum").deposit_token,
-                settings: RouteSettings::test_defaults(),
-            },
-            Route {
-                name: "ARB_TO_GNOSIS",
-                inbox_chain_id: 42161,
-                inbox_address: self.inbox_arb_to_gnosis,
-                inbox_provider: arb_provider.clone(),
-                inbox_avg_block_millis: 250,
-                outbox_chain_id: 100,
-                outbox_address: self.outbox_arb_to_gnosis,
-                outbox_provider: gnosis_provider.clone(),
-                weth_address: self.chains.get(&100).expect("Gnosis").deposit_token,
-                settings: RouteSettings::test_defaults(),
-            },
-        ]
-    }
-
-    fn parse_rpc_urls(env_var: &str) -> Vec<String> {
-        std::env::var(env_var)
-            .expect(&format!("{} must be set", env_var))
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
+        let mut providers = HashMap::new();
+        let wallet = self.address();
+        let recorder = self.dry_run.then(|| Arc::new(TxRecorder::default()));
+        let mut provider = |chain_id: u64| {
+            providers.entry(chain_id)
+                .or_insert_with(|| {
+                    let chain = &self.chains[&chain_id];
+                    let provider = self.build_provider(chain_id);
+                    let mut nonces = NonceManager::new(&chain.name, provider.clone(), wallet, self.tx, chain.avg_block_millis);
+                    if let Some(recorder) = &recorder {
+                        nonces = nonces.with_recorder(recorder.clone());
+                    }
+                    let chunks = LogChunker::new(&chain.name, chain.log_chunks);
+                    let blocks = BlockTimes::new(&chain.name, provider.clone(), chain.avg_block_millis);
+                    (provider, Arc::new(nonces), Arc::new(chunks), Arc::new(blocks))
+                })
+                .clone()
+        };
+
+        self.routes.iter()
+            .map(|r| {
+                let inbox_chain = self.chains.get(&r.inbox_chain_id).expect("Inbox chain not found");
+                let outbox_chain = self.chains.get(&r.outbox_chain_id).expect("Outbox chain not found");
+                let (inbox_provider, inbox_nonces, inbox_log_chunks, inbox_blocks) = provider(r.inbox_chain_id);
+                let (outbox_provider, outbox_nonces, outbox_log_chunks, outbox_blocks) = provider(r.outbox_chain_id);
+                Route {
+                    name: r.name.clone(),
+                    adapter: r.contracts.adapter(),
+                    inbox_chain_id: r.inbox_chain_id,
+                    inbox_chain_name: inbox_chain.name.clone(),
+                    inbox_address: r.inbox_address,
+                    inbox_provider,
+                    inbox_nonces,
+                    inbox_avg_block_millis: inbox_chain.avg_block_millis,
+                    inbox_fees: inbox_chain.fees,
+                    inbox_finality: inbox_chain.finality,
+                    inbox_log_chunks,
+                    inbox_blocks,
+                    outbox_chain_id: r.outbox_chain_id,
+                    outbox_chain_name: outbox_chain.name.clone(),
+                    outbox_native_currency: outbox_chain.native_currency.clone(),
+                    outbox_address: r.outbox_address,
+                    outbox_provider,
+                    outbox_nonces,
+                    outbox_fees: outbox_chain.fees,
+                    outbox_finality: outbox_chain.finality,
+                    outbox_log_chunks,
+                    outbox_blocks,
+                    weth_address: r.deposit_token.or(outbox_chain.deposit_token),
+                    claim_policy: self.claims,
+                    settings: RouteSettings::test_defaults(),
+                }
+            })
             .collect()
     }
 
+    /// Loads and validates the `ConfigFile` and the signing key. A dry run
+    /// needs no key if `VALIDATOR_ADDRESS` names the address to watch.
     pub fn from_env() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-
-        let arbitrum_rpcs = Self::parse_rpc_urls("ARBITRUM_RPC_URL");
-        let ethereum_rpcs = Self::parse_rpc_urls("ETHEREUM_RPC_URL");
-        let gnosis_rpcs = Self::parse_rpc_urls("GNOSIS_RPC_URL");
-        let weth_gnosis = Address::from_str(
-            &std::env::var("WETH_GNOSIS")
-                .expect("WETH_GNOSIS must be set")
-        )?;
-
-        let private_key = std::env::var("PRIVATE_KEY")
-            .or_else(|_| std::fs::read_to_string("/run/secrets/validator_key")
-                .map(|s| s.trim().to_string()))
-            .expect("PRIVATE_KEY not set or /run/secrets/validator_key not found");
+        let file = ConfigFile::from_env()?;
+        file.validate()?;
 
         use alloy::signers::local::PrivateKeySigner;
-        let signer = PrivateKeySigner::from_str(&private_key)?;
-        let wallet = EthereumWallet::from(signer);
-
-        let mut chains = HashMap::new();
-        chains.insert(42161, ChainInfo {
-            name: "Arbitrum".to_string(),
-            rpc_urls: arbitrum_rpcs,
-            deposit_token: None,
-            avg_block_millis: 250,
-        });
-        chains.insert(1, ChainInfo {
-            name: "Ethereum".to_string(),
-            rpc_urls: ethereum_rpcs,
-            deposit_token: None,
-            avg_block_millis: 12000,
-        });
-        chains.insert(100, ChainInfo {
-            name: "Gnosis".to_string(),
-            rpc_urls: gnosis_rpcs,
-            deposit_token: Some(weth_gnosis),
-            avg_block_millis: 5000,
-        });
-
-        let inbox_arb_to_eth = Address::from_str(
-            &std::env::var("VEA_INBOX_ARB_TO_ETH")
-                .expect("VEA_INBOX_ARB_TO_ETH must be set")
-        )?;
-        let outbox_arb_to_eth = Address::from_str(
-            &std::env::var("VEA_OUTBOX_ARB_TO_ETH")
-                .expect("VEA_OUTBOX_ARB_TO_ETH must be set")
-        )?;
-        let inbox_arb_to_gnosis = Address::from_str(
-            &std::env::var("VEA_INBOX_ARB_TO_GNOSIS")
-                .expect("VEA_INBOX_ARB_TO_GNOSIS must be set")
-        )?;
-        let outbox_arb_to_gnosis = Address::from_str(
-            &std::env::var("VEA_OUTBOX_ARB_TO_GNOSIS")
-                .expect("VEA_OUTBOX_ARB_TO_GNOSIS must be set")
-        )?;
-        let arb_outbox = Address::from_str(
-            &std::env::var("ARB_OUTBOX")
-                .expect("ARB_OUTBOX must be set")
-        )?;
-
-        let make_claims = std::env::var("MAKE_CLAIMS")
-            .map(|v| v.to_lowercase() == "true" || v == "1")
-            .expect("MAKE_CLAIMS must be set");
+        let wallet = match file.signer.load()? {
+            Some(wallet) => wallet,
+            None if file.dry_run && file.validator_address.is_some() => EthereumWallet::from(PrivateKeySigner::random()),
+            None => return Err("PRIVATE_KEY not set or /run/secrets/validator_key not found".into()),
+        };
 
         Ok(Self {
-            private_key,
             wallet,
-            chains,
-            inbox_arb_to_eth,
-            outbox_arb_to_eth,
-            inbox_arb_to_gnosis,
-            outbox_arb_to_gnosis,
-            arb_outbox,
-            make_claims,
+            signer: file.signer,
+            chains: file.chains,
+            routes: file.routes,
+            arb_outbox: file.arb_outbox,
+            arb_outbox_chain_id: file.arb_outbox_chain_id,
+            make_claims: file.make_claims.unwrap_or(false),
+            storage: file.storage,
+            metrics_addr: file.metrics_addr.map(|a| a.parse()).transpose()?,
+            admin_addr: file.admin_addr.map(|a| a.parse()).transpose()?,
+            supervisor: file.supervisor,
+            retry: file.retry,
+            deadlines: file.deadlines,
+            dispatcher: file.dispatcher,
+            tx: file.tx,
+            claims: file.claims,
+            dry_run: file.dry_run,
+            watch_address: file.validator_address,
+            piggyback_relays: file.piggyback_relays,
+            alerts: file.alerts,
         })
     }
 }
@@ -3,7 +3,7 @@
 use alloy::sol;
 
 sol! {
-    #[derive(Debug)]
+    #[derive(Debug, PartialEq, Eq)]
     struct Claim {
         bytes32 stateRoot;
         address claimer;
@@ -14,7 +14,7 @@ sol! {
         address challenger;
     }
 
-    #[derive(Debug)]
+    #[derive(Debug, PartialEq, Eq)]
     enum Party {
         None,
         Claimer,
@@ -49,6 +49,16 @@ sol! {
         function sendSnapshot(uint256 _epoch, Claim memory _claim) external;
     }
 
+    #[derive(Debug)]
+    interface IVeaOutboxOnL1 {
+        function resolveDisputedClaim(uint256 _epoch, bytes32 _stateRoot, Claim memory _claim) external;
+    }
+
+    #[derive(Debug)]
+    interface IRouterArbToGnosis {
+        function route(uint256 _epoch, bytes32 _stateroot, uint256 _gasLimit, Claim memory _claim) external;
+    }
+
     #[derive(Debug)]
     #[sol(rpc)]
     interface IVeaOutbox {
@@ -108,6 +118,7 @@ sol! {
         function epochNow() external view returns (uint256);
         function epochFinalized() external view returns (uint256);
         function sendSnapshot(uint256 _epoch, uint256 _gasLimit, Claim memory _claim) external;
+        function routerArbToGnosis() external view returns (address);
     }
 
     #[derive(Debug)]
@@ -1,66 +1,102 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use alloy::providers::Provider;
 use tokio::time::{sleep, Duration};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, instrument};
 
+use crate::alerts::{self, Alert, AlertKind};
 use crate::config::Route;
 use crate::tasks;
-use crate::tasks::{ClaimStore, TaskStore};
+use crate::tasks::fees::FeeTier;
+use crate::tasks::{ClaimStore, TaskError, TaskStore};
+use crate::sync::LockExt;
 
 const BEFORE_EPOCH_BUFFER: u64 = 60;
 const AFTER_EPOCH_BUFFER: u64 = 15 * 60;
 
 pub struct EpochWatcher {
     route: Route,
-    make_claims: bool,
+    make_claims: Arc<AtomicBool>,
     claim_store: Arc<Mutex<ClaimStore>>,
     task_store: Arc<Mutex<TaskStore>>,
+    shutdown: CancellationToken,
 }
 
 impl EpochWatcher {
     pub fn new(route: Route, make_claims: bool, claim_store: Arc<Mutex<ClaimStore>>, task_store: Arc<Mutex<TaskStore>>) -> Self {
+        Self::with_make_claims_flag(route, Arc::new(AtomicBool::new(make_claims)), claim_store, task_store)
+    }
+
+    /// Shares the `make_claims` switch with the admin API so it can be toggled at runtime.
+    pub fn with_make_claims_flag(route: Route, make_claims: Arc<AtomicBool>, claim_store: Arc<Mutex<ClaimStore>>, task_store: Arc<Mutex<TaskStore>>) -> Self {
         Self {
             route,
             make_claims,
             claim_store,
             task_store,
+            shutdown: CancellationToken::new(),
         }
     }
 
+    /// Stops `watch_epochs` between iterations once `shutdown` is cancelled.
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
     async fn get_current_timestamp(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
         let block = self.route.inbox_provider.get_block_by_number(Default::default()).await?.unwrap();
         Ok(block.header.timestamp)
     }
 
+    #[instrument(name = "epoch_watcher", skip_all)]
     pub async fn watch_epochs(&self, epoch_period: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let mut last_before_epoch: Option<u64> = None;
         let mut last_after_epoch: Option<u64> = None;
-        loop {
+        while !self.shutdown.is_cancelled() {
             let now = self.get_current_timestamp().await?;
             let current_epoch = now / epoch_period;
             let next_epoch_start = (current_epoch + 1) * epoch_period;
             let time_until_next_epoch = next_epoch_start.saturating_sub(now);
 
             if time_until_next_epoch <= BEFORE_EPOCH_BUFFER && last_before_epoch != Some(current_epoch) {
-                println!("[{}][EpochWatcher] Saving snapshot for epoch {}", self.route.name, current_epoch);
-                tasks::save_snapshot::execute(&self.route, current_epoch).await
+                info!(epoch = current_epoch, "Saving snapshot");
+                tasks::save_snapshot::execute(&self.route, current_epoch, self.route.inbox_fees.bid(FeeTier::Low, 100)).await
                     .unwrap_or_else(|e| panic!("[{}] FATAL: Failed to save snapshot for epoch {}: {}", self.route.name, current_epoch, e));
                 last_before_epoch = Some(current_epoch);
             }
 
-            if self.make_claims && self.task_store.lock().unwrap().is_on_sync() {
-                let time_since_epoch_start = now.saturating_sub(current_epoch * epoch_period);
-                if time_since_epoch_start >= AFTER_EPOCH_BUFFER && current_epoch > 0 {
-                    let prev_epoch = current_epoch - 1;
+            if self.make_claims.load(Ordering::Relaxed) && self.task_store.locked().is_on_sync() {
+                // The latest epoch that ended at least `claim_delay` ago.
+                let claim_delay = AFTER_EPOCH_BUFFER.max(self.route.claim_policy.claim_delay());
+                let claimable_epochs = now.saturating_sub(claim_delay) / epoch_period;
+                if claimable_epochs > 0 {
+                    let prev_epoch = claimable_epochs - 1;
                     if last_after_epoch != Some(prev_epoch) {
-                        println!("[{}][EpochWatcher] Checking claim for epoch {}", self.route.name, prev_epoch);
-                        tasks::claim::execute(&self.route, prev_epoch, &self.claim_store, now).await
-                            .unwrap_or_else(|e| panic!("[{}] FATAL: Failed to claim epoch {}: {}", self.route.name, prev_epoch, e));
-                        last_after_epoch = Some(prev_epoch);
+                        info!(epoch = prev_epoch, "Checking claim");
+                        // Left unmarked on failure so the next tick tries again.
+                        match tasks::claim::execute(&self.route, prev_epoch, &self.claim_store, now, self.route.outbox_fees.bid(FeeTier::Normal, 100)).await {
+                            Ok(()) | Err(TaskError::RaceLost) => last_after_epoch = Some(prev_epoch),
+                            Err(e) => {
+                                error!(epoch = prev_epoch, error = %e, kind = e.name(), "Failed to claim epoch, retrying");
+                                let kind = match e {
+                                    TaskError::InsufficientFunds(_) => AlertKind::LowBalance,
+                                    _ => AlertKind::ClaimFailed,
+                                };
+                                alerts::fire(Alert::new(kind, &self.route.name, prev_epoch, format!("Claim for epoch {} failed: {}", prev_epoch, e)));
+                            }
+                        }
                     }
                 }
             }
 
-            sleep(Duration::from_secs(10)).await;
+            tokio::select! {
+                _ = sleep(Duration::from_secs(10)) => {}
+                _ = self.shutdown.cancelled() => {}
+            }
         }
+        info!("Epoch watcher stopped");
+        Ok(())
     }
 }
@@ -1,62 +1,43 @@
 use alloy::primitives::{Address, Bytes, FixedBytes, U256};
 use alloy::providers::Provider;
+use alloy::eips::BlockNumberOrTag;
 use alloy::rpc::types::Filter;
 use std::cmp::min;
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
 use tokio::time::{sleep, Duration};
-
-use crate::config::Route;
-use crate::contracts::{IVeaInbox, IArbSys};
-use crate::tasks::{Task, TaskKind, TaskStore, ClaimStore, ClaimData};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, instrument, warn};
+
+use crate::adapters::ExpectedRelay;
+use crate::block_times::BlockTimes;
+use crate::alerts::{self, Alert, AlertKind};
+use crate::config::{Finality, Route};
+use crate::contracts::{IVeaInbox, IArbSys, Party};
+use crate::metrics;
+use crate::tasks::{Checkpoint, Task, TaskError, TaskKind, TaskStore, ClaimStore, ClaimData, IndexedChain, RouteState, Undo};
+use crate::tasks::storage::{self, StoreOp, MAX_CHECKPOINTS};
 
 use alloy::network::Ethereum;
 use alloy::providers::DynProvider;
+use crate::sync::LockExt;
 
-enum ScanTarget { Inbox, Outbox }
-
-const CHUNK_SIZE: u64 = 2000;
 const FINALITY_BUFFER_SECS: u64 = 15 * 60;
 const CATCHUP_SLEEP: Duration = Duration::from_secs(1);
 const IDLE_SLEEP: Duration = Duration::from_secs(5 * 60);
+/// Catching up without moving either cursor for this long counts as a stall.
+const STALL_ALERT_AFTER: Duration = Duration::from_secs(30 * 60);
 const ARB_SYS: Address = Address::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x64]);
 
-async fn get_log_timestamp(log: &alloy::rpc::types::Log, provider: &DynProvider<Ethereum>) -> u64 {
-    if let Some(ts) = log.block_timestamp {
-        return ts;
-    }
-    let block_num = log.block_number.expect("Log missing block_number");
-    let block = provider.get_block_by_number(block_num.into()).await
-        .expect("Failed to fetch block for timestamp")
-        .expect("Block not found");
-    block.header.timestamp
+async fn block_hash(provider: &DynProvider<Ethereum>, block: u64) -> Result<Option<FixedBytes<32>>, TaskError> {
+    Ok(provider.get_block_by_number(block.into()).await?.map(|b| b.header.hash))
 }
 
-async fn find_block_by_timestamp(provider: &DynProvider<Ethereum>, target_ts: u64) -> u64 {
-    let latest = provider.get_block_number().await.expect("Failed to get latest block number");
-    let latest_block = provider.get_block_by_number(latest.into()).await
-        .expect("Failed to get latest block")
-        .expect("Latest block not found");
-
-    if target_ts >= latest_block.header.timestamp {
-        return latest;
-    }
-
-    let mut lo = 0u64;
-    let mut hi = latest;
-
-    while lo < hi {
-        let mid = lo + (hi - lo) / 2;
-        let block = provider.get_block_by_number(mid.into()).await
-            .expect("Failed to get block during binary search")
-            .expect("Block not found during binary search");
-        if block.header.timestamp < target_ts {
-            lo = mid + 1;
-        } else {
-            hi = mid;
-        }
-    }
-    lo
+async fn tagged_block(provider: &DynProvider<Ethereum>, tag: BlockNumberOrTag) -> Result<u64, TaskError> {
+    provider.get_block_by_number(tag).await?
+        .map(|b| b.header.number)
+        .ok_or_else(|| TaskError::RpcUnavailable(format!("node does not serve the {} block", tag)))
 }
 
 pub struct EventIndexer {
@@ -64,8 +45,10 @@ pub struct EventIndexer {
     wallet_address: Address,
     task_store: Arc<Mutex<TaskStore>>,
     claim_store: Arc<Mutex<ClaimStore>>,
+    piggyback_relays: bool,
     inbox_catchup: (AtomicU64, AtomicU64, AtomicU64),
     outbox_catchup: (AtomicU64, AtomicU64, AtomicU64),
+    shutdown: CancellationToken,
 }
 
 impl EventIndexer {
@@ -80,31 +63,41 @@ impl EventIndexer {
             wallet_address,
             task_store,
             claim_store,
+            piggyback_relays: false,
             inbox_catchup: (AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)),
             outbox_catchup: (AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)),
+            shutdown: CancellationToken::new(),
         }
     }
 
-    pub async fn initialize(&self) {
-        self.task_store.lock().unwrap().set_on_sync(false);
-        let state = self.task_store.lock().unwrap().load();
+    /// Also relays `SnapshotSent` from other validators whose claim matches
+    /// ours, instead of waiting to send our own.
+    pub fn with_piggyback_relays(mut self, enabled: bool) -> Self {
+        self.piggyback_relays = enabled;
+        self
+    }
+
+    /// Stops `run` after the current scan once `shutdown` is cancelled.
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
 
-        let inbox_now = self.route.inbox_provider.get_block_by_number(Default::default()).await
-            .expect("Failed to get inbox block")
-            .expect("Inbox block not found")
+    #[instrument(name = "indexer", skip_all)]
+    pub async fn initialize(&self) -> Result<(), TaskError> {
+        self.task_store.locked().set_on_sync(false);
+        metrics::set_on_sync(&self.route.name, false);
+        let state = self.task_store.locked().load();
+
+        let inbox_now = self.route.inbox_provider.get_block_by_number(Default::default()).await?
+            .ok_or_else(|| TaskError::RpcUnavailable("inbox latest block not found".into()))?
             .header.timestamp;
 
         let needs_init = match (state.inbox_last_block, state.outbox_last_block) {
             (None, _) | (_, None) => true,
             (Some(inbox_b), Some(outbox_b)) => {
-                let inbox_ts = self.route.inbox_provider.get_block_by_number(inbox_b.into()).await
-                    .expect("Failed to get inbox last block")
-                    .expect("Inbox last block not found")
-                    .header.timestamp;
-                let outbox_ts = self.route.outbox_provider.get_block_by_number(outbox_b.into()).await
-                    .expect("Failed to get outbox last block")
-                    .expect("Outbox last block not found")
-                    .header.timestamp;
+                let inbox_ts = self.route.inbox_blocks.timestamp(inbox_b).await?;
+                let outbox_ts = self.route.outbox_blocks.timestamp(outbox_b).await?;
                 inbox_ts < inbox_now.saturating_sub(self.route.settings.sync_lookback_secs)
                     || outbox_ts < inbox_now.saturating_sub(self.route.settings.sync_lookback_secs)
             }
@@ -112,68 +105,129 @@ impl EventIndexer {
 
         if needs_init {
             let indexing_since = inbox_now.saturating_sub(self.route.settings.sync_lookback_secs);
-            let inbox_start = find_block_by_timestamp(&self.route.inbox_provider, indexing_since).await;
-            let outbox_start = find_block_by_timestamp(&self.route.outbox_provider, indexing_since).await;
-            self.task_store.lock().unwrap().initialize_sync(indexing_since, inbox_start, outbox_start);
-            println!("[{}][Indexer] Initialized sync: indexing_since={}, inbox_start={}, outbox_start={}",
-                self.route.name, indexing_since, inbox_start, outbox_start);
+            let inbox_start = self.route.inbox_blocks.find_block(indexing_since).await?;
+            let outbox_start = self.route.outbox_blocks.find_block(indexing_since).await?;
+            self.task_store.locked().initialize_sync(indexing_since, inbox_start, outbox_start);
+            info!(indexing_since, inbox_start, outbox_start, "Initialized sync");
         }
+        Ok(())
     }
 
     pub async fn run(&self) {
-        loop {
+        let mut last_progress = Instant::now();
+        let mut cursors = self.cursors();
+        while !self.shutdown.is_cancelled() {
             let done = self.scan_once().await;
-            if done {
-                if !self.task_store.lock().unwrap().is_on_sync() {
-                    println!("[{}][Indexer] Sync complete", self.route.name);
-                    self.task_store.lock().unwrap().set_on_sync(true);
+            let new_cursors = self.cursors();
+            if done || new_cursors != cursors {
+                last_progress = Instant::now();
+                cursors = new_cursors;
+            } else if last_progress.elapsed() >= STALL_ALERT_AFTER {
+                warn!(inbox_block = ?cursors.0, outbox_block = ?cursors.1, stalled_secs = last_progress.elapsed().as_secs(), "Indexer stalled");
+                alerts::fire(Alert::new(AlertKind::SyncStalled, &self.route.name, "indexer", format!(
+                    "indexer has not advanced for {}s (inbox block {:?}, outbox block {:?})",
+                    last_progress.elapsed().as_secs(), cursors.0, cursors.1,
+                )));
+            }
+            let pause = if done {
+                if !self.task_store.locked().is_on_sync() {
+                    info!("Sync complete");
+                    self.task_store.locked().set_on_sync(true);
+                    metrics::set_on_sync(&self.route.name, true);
                 }
-                sleep(IDLE_SLEEP).await;
+                IDLE_SLEEP
             } else {
-                sleep(CATCHUP_SLEEP).await;
+                CATCHUP_SLEEP
+            };
+            tokio::select! {
+                _ = sleep(pause) => {}
+                _ = self.shutdown.cancelled() => {}
             }
         }
+        info!("Indexer stopped");
+    }
+
+    fn cursors(&self) -> (Option<u64>, Option<u64>) {
+        let state = self.task_store.locked().load();
+        (state.inbox_last_block, state.outbox_last_block)
     }
 
+    #[instrument(name = "indexer", skip_all)]
     pub async fn scan_once(&self) -> bool {
-        let inbox_done = self.scan_chain(ScanTarget::Inbox).await;
-        let outbox_done = self.scan_chain(ScanTarget::Outbox).await;
+        let inbox_done = self.scan_chain(IndexedChain::Inbox).await;
+        let outbox_done = self.scan_chain(IndexedChain::Outbox).await;
         inbox_done && outbox_done
     }
 
-    async fn scan_chain(&self, target: ScanTarget) -> bool {
-        use ScanTarget::*;
+    async fn scan_chain(&self, target: IndexedChain) -> bool {
+        use IndexedChain::*;
 
-        let (provider, address, label, catchup) = match target {
-            Inbox => (&self.route.inbox_provider, self.route.inbox_address, "Inbox", &self.inbox_catchup),
-            Outbox => (&self.route.outbox_provider, self.route.outbox_address, "Outbox", &self.outbox_catchup),
+        let route = &self.route;
+        let (provider, blocks, address, label, catchup, finality, chunks) = match target {
+            Inbox => (&route.inbox_provider, &route.inbox_blocks, route.inbox_address, "Inbox", &self.inbox_catchup, route.inbox_finality, &route.inbox_log_chunks),
+            Outbox => (&route.outbox_provider, &route.outbox_blocks, route.outbox_address, "Outbox", &self.outbox_catchup, route.outbox_finality, &route.outbox_log_chunks),
         };
 
         let current_block = match provider.get_block_number().await {
             Ok(b) => b,
             Err(e) => {
-                eprintln!("[{}][Indexer] Failed to get {} block number: {}", self.route.name, label, e);
+                error!(chain = label, error = %e, "Failed to get block number");
                 return false;
             }
         };
 
-        let current_block_data = provider.get_block_by_number(current_block.into()).await
-            .expect("Failed to get block data")
-            .expect("Block not found");
-        let now = current_block_data.header.timestamp;
+        metrics::set_chain_head(&self.route.name, label, current_block);
+
+        let now = match blocks.timestamp(current_block).await {
+            Ok(ts) => ts,
+            Err(e) => {
+                error!(chain = label, block = current_block, error = %e, "Failed to get head timestamp");
+                return false;
+            }
+        };
 
-        let state = self.task_store.lock().unwrap().load();
+        let state = self.task_store.locked().load();
         let from_block = match target {
             Inbox => state.inbox_last_block.expect("inbox_last_block not set"),
             Outbox => state.outbox_last_block.expect("outbox_last_block not set"),
         };
 
+        // The cursor block was checkpointed when the previous scan ended on it;
+        // a different hash now means the logs derived since may be orphaned.
+        if let Some(checkpoint) = state.checkpoints.iter().rev().find(|c| c.chain == target)
+            && checkpoint.block == from_block
+        {
+            let hash = match block_hash(provider, from_block).await {
+                Ok(h) => h,
+                Err(e) => {
+                    error!(chain = label, block = from_block, error = %e, "Failed to get cursor block hash");
+                    return false;
+                }
+            };
+            if hash != Some(checkpoint.hash) {
+                if let Err(e) = self.roll_back(target, provider, &state).await {
+                    error!(chain = label, error = %e, "Failed to roll back reorged blocks, retrying");
+                }
+                return false;
+            }
+        }
+
         let (catchup_start, catchup_target, last_logged_pct) = catchup;
         let mut target_block = catchup_target.load(Ordering::Relaxed);
 
         if target_block == 0 || from_block >= target_block {
-            let target_ts = now.saturating_sub(FINALITY_BUFFER_SECS);
-            target_block = find_block_by_timestamp(provider, target_ts).await;
+            let finalized = match finality {
+                Finality::Buffer => blocks.find_block(now.saturating_sub(FINALITY_BUFFER_SECS)).await,
+                Finality::Safe => tagged_block(provider, BlockNumberOrTag::Safe).await,
+                Finality::Finalized => tagged_block(provider, BlockNumberOrTag::Finalized).await,
+            };
+            target_block = match finalized {
+                Ok(b) => b,
+                Err(e) => {
+                    error!(chain = label, error = %e, "Failed to get finalized block");
+                    return false;
+                }
+            };
             catchup_target.store(target_block, Ordering::Relaxed);
         }
 
@@ -189,7 +243,8 @@ impl EventIndexer {
             last_logged_pct.store(0, Ordering::Relaxed);
         }
 
-        let to_block = min(from_block + CHUNK_SIZE, target_block);
+        let chunk = chunks.size();
+        let to_block = min(from_block + chunk, target_block);
 
         let event_sigs: Vec<FixedBytes<32>> = match target {
             Inbox => vec![alloy::primitives::keccak256("SnapshotSent(uint256,bytes32)")],
@@ -207,23 +262,33 @@ impl EventIndexer {
             .from_block(from_block)
             .to_block(to_block);
 
+        // Taken before the logs so a reorg racing the query leaves a stale
+        // hash, which the next scan catches, rather than a fresh one.
+        let to_hash = match block_hash(provider, to_block).await {
+            Ok(Some(h)) => h,
+            Ok(None) => {
+                warn!(chain = label, to_block, "Block disappeared before scanning, retrying");
+                return false;
+            }
+            Err(e) => {
+                error!(chain = label, to_block, error = %e, "Failed to get block hash");
+                return false;
+            }
+        };
+
+        let started = Instant::now();
         match provider.get_logs(&filter).await {
             Ok(logs) => {
+                chunks.record_success(to_block - from_block, logs.len(), started.elapsed());
                 for log in logs {
-                    let block_ts = get_log_timestamp(&log, provider).await;
-                    if block_ts > now.saturating_sub(FINALITY_BUFFER_SECS) {
-                        continue;
-                    }
-                    match target {
-                        Inbox => self.handle_snapshot_sent(&log).await,
-                        Outbox => self.dispatch_outbox_event(&log).await,
+                    if let Err(e) = self.handle_log(target, &log, blocks, finality, now).await {
+                        warn!(chain = label, from_block, to_block, error = %e, "Failed to handle log, retrying");
+                        return false;
                     }
                 }
 
-                match target {
-                    Inbox => self.task_store.lock().unwrap().update_inbox_block(to_block),
-                    Outbox => self.task_store.lock().unwrap().update_outbox_block(to_block),
-                }
+                self.task_store.locked().advance_cursor(target, to_block, to_hash);
+                metrics::set_indexer_block(&self.route.name, label, to_block);
 
                 let is_done = to_block >= target_block;
 
@@ -231,10 +296,10 @@ impl EventIndexer {
                 if start != 0 && !is_done {
                     let total = target_block.saturating_sub(start);
                     let done = to_block.saturating_sub(start);
-                    let pct = if total > 0 { (done * 100) / total } else { 100 };
+                    let pct = (done * 100).checked_div(total).unwrap_or(100);
                     let last_pct = last_logged_pct.load(Ordering::Relaxed);
                     if pct / 10 > last_pct / 10 {
-                        println!("[{}][Indexer] {} sync {}%", self.route.name, label, pct);
+                        info!(chain = label, progress_pct = pct, "Sync progress");
                         last_logged_pct.store(pct, Ordering::Relaxed);
                     }
                 }
@@ -246,63 +311,206 @@ impl EventIndexer {
                 is_done
             }
             Err(e) => {
-                eprintln!("[{}][Indexer] Failed to query {} logs {}-{}: {}", self.route.name, label, from_block, to_block, e);
+                if chunks.record_error(&e.to_string()) {
+                    warn!(chain = label, from_block, to_block, error = %e, "Log query range refused, retrying with a smaller one");
+                } else {
+                    error!(chain = label, from_block, to_block, error = %e, "Failed to query logs");
+                }
                 false
             }
         }
     }
 
-    async fn dispatch_outbox_event(&self, log: &alloy::rpc::types::Log) {
+    /// Skips logs still inside the finality buffer ending at `now`. Errs on
+    /// RPC failures, leaving the log to be retried.
+    async fn handle_log(&self, target: IndexedChain, log: &alloy::rpc::types::Log, blocks: &BlockTimes, finality: Finality, now: u64) -> Result<(), TaskError> {
+        if finality == Finality::Buffer && blocks.log_timestamp(log).await? > now.saturating_sub(FINALITY_BUFFER_SECS) {
+            return Ok(());
+        }
+        match target {
+            IndexedChain::Inbox => self.handle_snapshot_sent(log).await,
+            IndexedChain::Outbox => self.dispatch_outbox_event(log).await,
+        }
+    }
+
+    /// Commits `ops` derived from a log on `chain`, queueing their undo
+    /// entries for the chain's next checkpoint in the same batch.
+    fn record(&self, chain: IndexedChain, mut ops: Vec<StoreOp>) {
+        let undo = {
+            let claims = self.claim_store.locked();
+            let tasks = self.task_store.locked();
+            ops.iter().rev().filter_map(|op| match op {
+                StoreOp::AddTask(task) => Some(Undo::RemoveTask { epoch: task.epoch, kind: task.kind.name().to_string() }),
+                StoreOp::RemoveTask { epoch, kind } => tasks.find_task(*epoch, kind).map(|task| Undo::RestoreTask { task }),
+                StoreOp::StoreClaim(claim) => Some(Undo::RemoveClaim { epoch: claim.epoch }),
+                StoreOp::ReplaceClaim(claim) => claims.backend().get_claim(claim.epoch).map(|claim| Undo::RestoreClaim { claim }),
+                _ => None,
+            }).collect::<Vec<_>>()
+        };
+        ops.push(StoreOp::RecordUndo(chain, undo));
+        storage::commit(&self.claim_store, &self.task_store, ops);
+    }
+
+    /// Walks `chain`'s checkpoints back to the newest one still on the
+    /// canonical chain, undoes what was derived after it and moves the
+    /// cursor there, so the next scan re-indexes the new fork. Tasks the
+    /// dispatcher later derived from an undone claim are dropped with it.
+    async fn roll_back(&self, chain: IndexedChain, provider: &DynProvider<Ethereum>, state: &RouteState) -> Result<(), TaskError> {
+        let mut undo: Vec<Undo> = state.pending_undo.iter().rev()
+            .filter(|(c, _)| *c == chain)
+            .map(|(_, u)| u.clone())
+            .collect();
+        let checkpoints: Vec<&Checkpoint> = state.checkpoints.iter().rev().filter(|c| c.chain == chain).collect();
+        let mut fork_block = None;
+        for checkpoint in &checkpoints {
+            if block_hash(provider, checkpoint.block).await? == Some(checkpoint.hash) {
+                fork_block = Some(checkpoint.block);
+                break;
+            }
+            undo.extend(checkpoint.undo.iter().rev().cloned());
+        }
+        // Deeper than the retained checkpoints: keep what the oldest one
+        // covers and re-scan from it, dropping it so its stale hash does not
+        // trigger another roll back.
+        let (fork_block, keep_after) = match fork_block {
+            Some(block) => (block, block),
+            None => {
+                let oldest = checkpoints.last().expect("Reorg detected without a checkpoint");
+                undo.truncate(undo.len() - oldest.undo.len());
+                error!(chain = chain.name(), fork_block = oldest.block, checkpoints = MAX_CHECKPOINTS, "Reorg deeper than the retained checkpoints, re-scanning from the oldest");
+                (oldest.block, oldest.block.saturating_sub(1))
+            }
+        };
+
+        let mut ops: Vec<StoreOp> = {
+            let claims = self.claim_store.locked();
+            let tasks = self.task_store.locked();
+            undo.into_iter().filter_map(|u| match u {
+                Undo::RemoveTask { epoch, kind } => TaskKind::NAMES.iter()
+                    .find(|name| **name == kind)
+                    .map(|kind| StoreOp::RemoveTask { epoch, kind }),
+                Undo::RestoreTask { task } => tasks.find_task(task.epoch, task.kind.name()).is_none()
+                    .then_some(StoreOp::AddTask(task)),
+                Undo::RemoveClaim { epoch } => Some(StoreOp::RemoveClaim(epoch)),
+                Undo::RestoreClaim { claim } => claims.exists(claim.epoch)
+                    .then_some(StoreOp::ReplaceClaim(claim)),
+            }).collect()
+        };
+        let orphaned: Vec<u64> = ops.iter()
+            .filter_map(|op| match op { StoreOp::RemoveClaim(epoch) => Some(*epoch), _ => None })
+            .collect();
+        for task in state.tasks.iter().filter(|t| orphaned.contains(&t.epoch) && t.kind.follows_claim()) {
+            let kind = task.kind.name();
+            if !ops.iter().any(|op| matches!(op, StoreOp::RemoveTask { epoch, kind: k } if *epoch == task.epoch && *k == kind)) {
+                ops.push(StoreOp::RemoveTask { epoch: task.epoch, kind });
+            }
+        }
+        let undone = ops.len();
+        ops.push(StoreOp::RollBack { chain, block: keep_after });
+        ops.push(match chain {
+            IndexedChain::Inbox => StoreOp::SetInboxBlock(fork_block),
+            IndexedChain::Outbox => StoreOp::SetOutboxBlock(fork_block),
+        });
+        let cursor = match chain {
+            IndexedChain::Inbox => state.inbox_last_block,
+            IndexedChain::Outbox => state.outbox_last_block,
+        };
+        warn!(chain = chain.name(), from_block = ?cursor, fork_block, undone, "Reorg detected, rolling back");
+        metrics::record_reorg(&self.route.name, chain.name());
+        storage::commit(&self.claim_store, &self.task_store, ops);
+        Ok(())
+    }
+
+    /// Errs on RPC failures, leaving the log to be retried.
+    async fn dispatch_outbox_event(&self, log: &alloy::rpc::types::Log) -> Result<(), TaskError> {
         let topic0 = match log.topics().first() {
             Some(t) => *t,
-            None => return,
+            None => return Ok(()),
         };
 
         if topic0 == alloy::primitives::keccak256("Claimed(address,uint256,bytes32)") {
-            self.handle_claimed_event(log).await;
+            self.handle_claimed_event(log).await
         } else if topic0 == alloy::primitives::keccak256("VerificationStarted(uint256)") {
-            self.handle_verification_started_event(log).await;
+            self.handle_verification_started_event(log).await
         } else if topic0 == alloy::primitives::keccak256("Challenged(uint256,address)") {
-            self.handle_challenged_event(log).await;
+            self.handle_challenged_event(log).await
         } else if topic0 == alloy::primitives::keccak256("Verified(uint256)") {
-            self.handle_verified_event(log).await;
+            self.handle_verified_event(log).await
+        } else {
+            Ok(())
         }
     }
 
-    async fn handle_snapshot_sent(&self, log: &alloy::rpc::types::Log) {
+    /// Errs on RPC failures checking the relay, leaving the log to be retried.
+    async fn handle_snapshot_sent(&self, log: &alloy::rpc::types::Log) -> Result<(), TaskError> {
         let epoch = match self.parse_epoch_from_snapshot_sent(log) {
             Some(e) => e,
-            None => return,
+            None => return Ok(()),
         };
 
-        let state = self.task_store.lock().unwrap().load();
+        let state = self.task_store.locked().load();
         if state.tasks.iter().any(|t| t.epoch == epoch && matches!(t.kind, TaskKind::ExecuteRelay { .. })) {
-            return;
+            return Ok(());
         }
 
         let tx_hash = match log.transaction_hash {
             Some(h) => h,
-            None => return,
+            None => return Ok(()),
         };
 
-        let tx = self.route.inbox_provider.get_transaction_by_hash(tx_hash).await
-            .expect("Failed to get transaction")
-            .expect("Transaction not found");
-        if tx.inner.signer() != self.wallet_address {
-            println!("[{}][Indexer] SnapshotSent for epoch {} not from validator, skipping", self.route.name, epoch);
-            return;
+        let tx = self.route.inbox_provider.get_transaction_by_hash(tx_hash).await?
+            .ok_or_else(|| TaskError::RpcUnavailable(format!("transaction {} not found", tx_hash)))?;
+        let from_us = tx.inner.signer() == self.wallet_address;
+        if !from_us && !self.piggyback_relays {
+            info!(epoch, tx_hash = %tx_hash, "SnapshotSent not from validator, skipping");
+            return Ok(());
+        }
+
+        // Our own snapshots are relayed as before; another validator's only
+        // for a claim we indexed that is still unresolved.
+        if !from_us {
+            let claim_store = self.claim_store.locked();
+            if !claim_store.exists(epoch) {
+                warn!(epoch, tx_hash = %tx_hash, "Dropping SnapshotSent - claim outside sync window");
+                return Ok(());
+            }
+            if claim_store.get_claim(epoch).honest != Party::None {
+                info!(epoch, tx_hash = %tx_hash, "SnapshotSent for an already resolved claim, skipping");
+                return Ok(());
+            }
         }
 
-        match self.fetch_l2_to_l1_from_tx(tx_hash, epoch).await {
+        match self.fetch_l2_to_l1_from_tx(tx_hash, epoch).await? {
             Some(task) => {
-                println!(
-                    "[{}][Indexer] Found SnapshotSent: epoch={}, position={:#x}",
-                    self.route.name, epoch, task.2
-                );
-                self.task_store.lock().unwrap().add_task(Task {
-                    epoch: task.0,
-                    execute_after: task.1,
-                    kind: TaskKind::ExecuteRelay {
+                let inbox = IVeaInbox::new(self.route.inbox_address, self.route.inbox_provider.clone());
+                // Always indexed for another validator's snapshot; ours may
+                // predate the sync window. `isSpent` in `execute_relay`
+                // catches duplicates.
+                let claim = {
+                    let claim_store = self.claim_store.locked();
+                    claim_store.exists(epoch).then(|| claim_store.get_claim(epoch))
+                };
+                let expected = ExpectedRelay {
+                    destination: self.route.adapter.relay_destination(&self.route).await?,
+                    epoch,
+                    state_root: inbox.snapshots(U256::from(epoch)).call().await?,
+                    claim,
+                };
+                if let Err(mismatch) = expected.check(self.route.adapter.as_ref(), task.4, &task.9) {
+                    error!(epoch, tx_hash = %tx_hash, from_us, mismatch = %mismatch, "SnapshotSent payload does not match our claim, not relaying");
+                    alerts::fire(Alert::new(AlertKind::RelayMismatch, &self.route.name, epoch, format!("SnapshotSent {}: {}", tx_hash, mismatch)));
+                    return Ok(());
+                }
+                info!(epoch, tx_hash = %tx_hash, from_us, position = %format_args!("{:#x}", task.2), "Found SnapshotSent");
+                let mut ops = Vec::new();
+                if !from_us && self.task_store.locked().find_task(epoch, "SendSnapshot").is_some() {
+                    info!(epoch, "Snapshot already sent by another validator, dropping our SendSnapshot");
+                    ops.push(StoreOp::RemoveTask { epoch, kind: "SendSnapshot" });
+                }
+                ops.push(StoreOp::AddTask(Task::new(
+                    task.0,
+                    task.1,
+                    TaskKind::ExecuteRelay {
                         position: task.2,
                         l2_sender: task.3,
                         dest_addr: task.4,
@@ -312,150 +520,158 @@ impl EventIndexer {
                         amount: task.8,
                         data: task.9,
                     },
-                });
+                )));
+                self.record(IndexedChain::Inbox, ops);
             }
             None => {
-                eprintln!("[{}][Indexer] No L2ToL1Tx found in tx {:?}", self.route.name, tx_hash);
+                error!(epoch, tx_hash = %tx_hash, "No L2ToL1Tx found in tx");
             }
         }
+        Ok(())
     }
 
-    async fn handle_claimed_event(&self, log: &alloy::rpc::types::Log) {
+    async fn handle_claimed_event(&self, log: &alloy::rpc::types::Log) -> Result<(), TaskError> {
         if log.topics().len() < 3 {
-            return;
+            return Ok(());
         }
 
         let claimer = Address::from_slice(&log.topics()[1].0[12..]);
         let epoch = U256::from_be_bytes(log.topics()[2].0).to::<u64>();
-        println!("[{}][Indexer] Claimed event for epoch {} at block {}", self.route.name, epoch, log.block_number.unwrap_or(0));
+        info!(epoch, block = log.block_number.unwrap_or(0), "Claimed event");
 
         if log.data().data.len() < 32 {
-            return;
+            return Ok(());
         }
         let state_root = FixedBytes::<32>::from_slice(&log.data().data[0..32]);
 
-        let block_ts = get_log_timestamp(log, &self.route.outbox_provider).await;
+        let block_ts = self.route.outbox_blocks.log_timestamp(log).await?;
         let timestamp_claimed = block_ts as u32;
 
-        let state = self.task_store.lock().unwrap().load();
+        // Re-indexing after a resync sees claims whose tasks are long done.
+        if self.claim_store.locked().exists(epoch) {
+            info!(epoch, "Claim already indexed, skipping");
+            return Ok(());
+        }
+        let state = self.task_store.locked().load();
         if state.tasks.iter().any(|t| t.epoch == epoch) {
-            return;
+            return Ok(());
         }
 
-        self.claim_store.lock().unwrap().store(ClaimData {
-            epoch,
-            state_root,
-            claimer,
-            timestamp_claimed,
-            timestamp_verification: 0,
-            blocknumber_verification: 0,
-            honest: "None".to_string(),
-            challenger: Address::ZERO,
-        });
-
-        self.task_store.lock().unwrap().add_task(Task {
-            epoch,
-            execute_after: block_ts,
-            kind: TaskKind::ValidateClaim,
-        });
+        self.record(IndexedChain::Outbox, vec![
+            StoreOp::StoreClaim(ClaimData {
+                epoch,
+                state_root,
+                claimer,
+                timestamp_claimed,
+                timestamp_verification: 0,
+                blocknumber_verification: 0,
+                honest: "None".to_string(),
+                challenger: Address::ZERO,
+            }),
+            StoreOp::AddTask(Task::new(epoch, block_ts, TaskKind::ValidateClaim)),
+        ]);
+        Ok(())
     }
 
-    async fn handle_verification_started_event(&self, log: &alloy::rpc::types::Log) {
+    async fn handle_verification_started_event(&self, log: &alloy::rpc::types::Log) -> Result<(), TaskError> {
         if log.topics().len() < 2 {
-            return;
+            return Ok(());
         }
 
         let epoch = U256::from_be_bytes(log.topics()[1].0).to::<u64>();
-        println!("[{}][Indexer] VerificationStarted event for epoch {} at block {}", self.route.name, epoch, log.block_number.unwrap_or(0));
+        info!(epoch, block = log.block_number.unwrap_or(0), "VerificationStarted event");
 
-        if !self.claim_store.lock().unwrap().exists(epoch) {
-            let block_ts = get_log_timestamp(log, &self.route.outbox_provider).await;
-            let state = self.task_store.lock().unwrap().load();
+        if !self.claim_store.locked().exists(epoch) {
+            let block_ts = self.route.outbox_blocks.log_timestamp(log).await?;
+            let state = self.task_store.locked().load();
             let grace_end = state.indexing_since.unwrap_or(0) + self.route.settings.sync_lookback_secs;
 
             if block_ts < grace_end {
-                println!("[{}][Indexer] Dropping VerificationStarted for epoch {} - claim outside sync window", self.route.name, epoch);
-                return;
+                warn!(epoch, "Dropping VerificationStarted - claim outside sync window");
+                return Ok(());
             }
             panic!("[{}] VerificationStarted for epoch {} but claim not found - this is a bug", self.route.name, epoch);
         }
 
-        let block_ts = get_log_timestamp(log, &self.route.outbox_provider).await as u32;
+        let block_ts = self.route.outbox_blocks.log_timestamp(log).await? as u32;
         let block_num = log.block_number.expect("Log missing block_number") as u32;
 
-        self.claim_store.lock().unwrap().update(epoch, |c| {
+        let update = self.claim_store.locked().prepare_update(epoch, |c| {
             c.timestamp_verification = block_ts;
             c.blocknumber_verification = block_num;
         });
 
         let execute_after = (block_ts as u64) + self.route.settings.min_challenge_period;
 
-        self.task_store.lock().unwrap().add_task(Task {
-            epoch,
-            execute_after,
-            kind: TaskKind::VerifySnapshot,
-        });
+        self.record(IndexedChain::Outbox, vec![
+            update,
+            StoreOp::AddTask(
+                Task::new(epoch, execute_after, TaskKind::VerifySnapshot)
+                    .with_deadline(self.route.settings.verify_deadline(execute_after)),
+            ),
+        ]);
+        Ok(())
     }
 
-    async fn handle_challenged_event(&self, log: &alloy::rpc::types::Log) {
+    async fn handle_challenged_event(&self, log: &alloy::rpc::types::Log) -> Result<(), TaskError> {
         if log.topics().len() < 3 {
-            return;
+            return Ok(());
         }
 
         let epoch = U256::from_be_bytes(log.topics()[1].0).to::<u64>();
         let challenger = Address::from_slice(&log.topics()[2].0[12..]);
-        println!("[{}][Indexer] Challenged event for epoch {} at block {}", self.route.name, epoch, log.block_number.unwrap_or(0));
+        info!(epoch, block = log.block_number.unwrap_or(0), "Challenged event");
 
-        if !self.claim_store.lock().unwrap().exists(epoch) {
-            let block_ts = get_log_timestamp(log, &self.route.outbox_provider).await;
-            let state = self.task_store.lock().unwrap().load();
+        if !self.claim_store.locked().exists(epoch) {
+            let block_ts = self.route.outbox_blocks.log_timestamp(log).await?;
+            let state = self.task_store.locked().load();
             let grace_end = state.indexing_since.unwrap_or(0) + self.route.settings.sync_lookback_secs;
 
             if block_ts < grace_end {
-                println!("[{}][Indexer] Dropping Challenged for epoch {} - claim outside sync window", self.route.name, epoch);
-                return;
+                warn!(epoch, "Dropping Challenged - claim outside sync window");
+                return Ok(());
             }
             panic!("[{}] Challenged for epoch {} but claim not found - this is a bug", self.route.name, epoch);
         }
 
-        self.claim_store.lock().unwrap().update(epoch, |c| {
+        let update = self.claim_store.locked().prepare_update(epoch, |c| {
             c.challenger = challenger;
         });
 
-        let block_ts = get_log_timestamp(log, &self.route.outbox_provider).await;
-        self.task_store.lock().unwrap().add_task(Task {
-            epoch,
-            execute_after: block_ts,
-            kind: TaskKind::SendSnapshot,
-        });
+        let block_ts = self.route.outbox_blocks.log_timestamp(log).await?;
+        self.record(IndexedChain::Outbox, vec![
+            update,
+            StoreOp::AddTask(Task::new(epoch, block_ts, TaskKind::SendSnapshot)),
+        ]);
+        Ok(())
     }
 
-    async fn handle_verified_event(&self, log: &alloy::rpc::types::Log) {
+    async fn handle_verified_event(&self, log: &alloy::rpc::types::Log) -> Result<(), TaskError> {
         let epoch = if log.topics().len() >= 2 {
             U256::from_be_bytes(log.topics()[1].0).to::<u64>()
         } else if log.data().data.len() >= 32 {
             U256::from_be_slice(&log.data().data[0..32]).to::<u64>()
         } else {
-            return;
+            return Ok(());
         };
-        println!("[{}][Indexer] Verified event for epoch {} at block {}", self.route.name, epoch, log.block_number.unwrap_or(0));
+        info!(epoch, block = log.block_number.unwrap_or(0), "Verified event");
 
-        let block_ts = get_log_timestamp(log, &self.route.outbox_provider).await;
+        let block_ts = self.route.outbox_blocks.log_timestamp(log).await?;
 
-        if !self.claim_store.lock().unwrap().exists(epoch) {
-            let state = self.task_store.lock().unwrap().load();
+        if !self.claim_store.locked().exists(epoch) {
+            let state = self.task_store.locked().load();
             let grace_end = state.indexing_since.unwrap_or(0) + self.route.settings.sync_lookback_secs;
 
             if block_ts < grace_end {
-                println!("[{}][Indexer] Dropping Verified for epoch {} - claim outside sync window", self.route.name, epoch);
-                return;
+                warn!(epoch, "Dropping Verified - claim outside sync window");
+                return Ok(());
             }
             panic!("[{}] Verified for epoch {} but claim not found - this is a bug", self.route.name, epoch);
         }
 
-        let claim = self.claim_store.lock().unwrap().get(epoch);
+        let claim = self.claim_store.locked().get(epoch);
 
-        let real_state_root = self.get_inbox_snapshot(epoch).await;
+        let real_state_root = self.get_inbox_snapshot(epoch).await?;
 
         let honest = if claim.state_root == real_state_root {
             "Claimer"
@@ -463,15 +679,15 @@ impl EventIndexer {
             "Challenger"
         };
 
-        self.claim_store.lock().unwrap().update(epoch, |c| {
+        let update = self.claim_store.locked().prepare_update(epoch, |c| {
             c.honest = honest.to_string();
         });
 
-        self.task_store.lock().unwrap().add_task(Task {
-            epoch,
-            execute_after: block_ts,
-            kind: TaskKind::WithdrawDeposit,
-        });
+        self.record(IndexedChain::Outbox, vec![
+            update,
+            StoreOp::AddTask(Task::new(epoch, block_ts, TaskKind::WithdrawDeposit)),
+        ]);
+        Ok(())
     }
 
     fn parse_epoch_from_snapshot_sent(&self, log: &alloy::rpc::types::Log) -> Option<u64> {
@@ -485,10 +701,9 @@ impl EventIndexer {
         &self,
         tx_hash: FixedBytes<32>,
         epoch: u64,
-    ) -> Option<(u64, u64, U256, Address, Address, u64, u64, u64, U256, Bytes)> {
-        let receipt = self.route.inbox_provider.get_transaction_receipt(tx_hash).await
-            .expect("Failed to get transaction receipt")
-            .expect("Transaction receipt not found");
+    ) -> Result<Option<(u64, u64, U256, Address, Address, u64, u64, u64, U256, Bytes)>, TaskError> {
+        let receipt = self.route.inbox_provider.get_transaction_receipt(tx_hash).await?
+            .ok_or_else(|| TaskError::RpcUnavailable(format!("receipt for {} not found", tx_hash)))?;
 
         for log in receipt.inner.logs() {
             if log.address() != ARB_SYS {
@@ -499,14 +714,15 @@ impl EventIndexer {
                 Ok(d) => d,
                 Err(_) => continue,
             };
+            if decoded.inner.data.caller != self.route.inbox_address {
+                continue;
+            }
 
-            let block_number = receipt.block_number.expect("Receipt missing block_number");
-            let block_timestamp = self.route.inbox_provider.get_block_by_number(block_number.into()).await
-                .expect("Failed to get block by number")
-                .expect("Block not found")
-                .header.timestamp;
+            let block_number = receipt.block_number
+                .ok_or_else(|| TaskError::RpcUnavailable(format!("receipt for {} has no block number", tx_hash)))?;
+            let block_timestamp = self.route.inbox_blocks.timestamp(block_number).await?;
 
-            return Some((
+            return Ok(Some((
                 epoch,
                 block_timestamp + self.route.settings.relay_delay_secs,
                 decoded.inner.position,
@@ -517,13 +733,13 @@ impl EventIndexer {
                 decoded.inner.data.timestamp.to::<u64>(),
                 decoded.inner.data.callvalue,
                 decoded.inner.data.data.clone(),
-            ));
+            )));
         }
-        None
+        Ok(None)
     }
 
-    async fn get_inbox_snapshot(&self, epoch: u64) -> FixedBytes<32> {
+    async fn get_inbox_snapshot(&self, epoch: u64) -> Result<FixedBytes<32>, TaskError> {
         let inbox = IVeaInbox::new(self.route.inbox_address, self.route.inbox_provider.clone());
-        inbox.snapshots(U256::from(epoch)).call().await.expect("Failed to get inbox snapshot")
+        Ok(inbox.snapshots(U256::from(epoch)).call().await?)
     }
 }
@@ -1,6 +1,17 @@
+pub mod adapters;
+pub mod alerts;
+pub mod block_times;
+pub mod admin;
+pub mod cli;
 pub mod contracts;
 pub mod config;
 pub mod startup;
 pub mod tasks;
 pub mod epoch_watcher;
 pub mod indexer;
+pub mod logging;
+pub mod log_chunks;
+pub mod metrics;
+pub mod signer;
+pub mod supervisor;
+pub mod sync;
@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::info;
+
+use crate::metrics;
+use crate::sync::LockExt;
+
+/// Bounds of the block ranges the indexer asks `eth_getLogs` for on a chain.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogChunkConfig {
+    pub initial_blocks: u64,
+    pub min_blocks: u64,
+    pub max_blocks: u64,
+    /// A full chunk with fewer logs than this, answered within `fast_millis`,
+    /// doubles the next one.
+    pub grow_below_logs: usize,
+    pub fast_millis: u64,
+}
+
+impl Default for LogChunkConfig {
+    fn default() -> Self {
+        Self {
+            initial_blocks: 2000,
+            min_blocks: 10,
+            max_blocks: 100_000,
+            grow_below_logs: 1000,
+            fast_millis: 2000,
+        }
+    }
+}
+
+/// Why a provider refused a log query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeError {
+    /// The range matched more logs than the provider returns at once.
+    TooManyResults,
+    /// The range spans more blocks than the provider allows.
+    RangeTooLarge,
+}
+
+impl RangeError {
+    pub fn classify(message: &str) -> Option<Self> {
+        let message = message.to_lowercase();
+        const TOO_MANY: [&str; 4] = ["too many results", "query returned more than", "response size", "logs matched"];
+        const TOO_LARGE: [&str; 4] = ["block range", "range is too large", "range too large", "limited to a"];
+        if TOO_MANY.iter().any(|p| message.contains(p)) {
+            Some(RangeError::TooManyResults)
+        } else if TOO_LARGE.iter().any(|p| message.contains(p)) {
+            Some(RangeError::RangeTooLarge)
+        } else {
+            None
+        }
+    }
+}
+
+/// Largest decimal number in `message` below `size`, e.g. the 10,000 in
+/// "eth_getLogs is limited to a 10,000 range".
+fn stated_limit(message: &str, size: u64) -> Option<u64> {
+    message.split(|c: char| c.is_whitespace() || "()[]:=".contains(c))
+        .filter_map(|word| word.trim_end_matches(['.', ';']).replace(',', "").parse::<u64>().ok())
+        .filter(|n| *n > 0 && *n < size)
+        .max()
+}
+
+struct ChunkState {
+    size: u64,
+    /// Largest range to try again, below any the provider refused.
+    ceiling: u64,
+}
+
+/// The log-query range for one provider, grown while queries come back small
+/// and fast and shrunk when the provider refuses them. Shared by every route
+/// scanning the chain.
+pub struct LogChunker {
+    chain: String,
+    config: LogChunkConfig,
+    state: Mutex<ChunkState>,
+}
+
+impl LogChunker {
+    pub fn new(chain: &str, config: LogChunkConfig) -> Self {
+        let size = config.initial_blocks.clamp(config.min_blocks, config.max_blocks);
+        metrics::set_log_chunk(chain, size);
+        Self {
+            chain: chain.to_string(),
+            config,
+            state: Mutex::new(ChunkState { size, ceiling: config.max_blocks }),
+        }
+    }
+
+    /// Blocks past `from_block` the next query should cover.
+    pub fn size(&self) -> u64 {
+        self.state.locked().size
+    }
+
+    /// Grows the range after a full-size query returned `logs` within `elapsed`.
+    pub fn record_success(&self, blocks: u64, logs: usize, elapsed: Duration) {
+        let mut state = self.state.locked();
+        if blocks < state.size || logs >= self.config.grow_below_logs || elapsed.as_millis() >= self.config.fast_millis as u128 {
+            return;
+        }
+        let size = state.size.saturating_mul(2).min(state.ceiling);
+        if size != state.size {
+            state.size = size;
+            metrics::set_log_chunk(&self.chain, size);
+        }
+    }
+
+    /// Shrinks the range if `error` says it was too large. Returns false for
+    /// other errors, or when the range cannot shrink any further.
+    pub fn record_error(&self, error: &str) -> bool {
+        let Some(kind) = RangeError::classify(error) else { return false };
+        let mut state = self.state.locked();
+        if state.size <= self.config.min_blocks {
+            return false;
+        }
+        let size = match kind {
+            RangeError::TooManyResults => state.size / 2,
+            RangeError::RangeTooLarge => {
+                // Stay below the refused size from now on, within the
+                // provider's stated limit if it gives one (a range of N
+                // blocks spans N - 1 past `from_block`).
+                state.ceiling = stated_limit(error, state.size).map(|n| n - 1).unwrap_or(state.size / 2).max(self.config.min_blocks);
+                state.ceiling
+            }
+        };
+        state.size = size.clamp(self.config.min_blocks, state.ceiling);
+        info!(chain = self.chain, blocks = state.size, ceiling = state.ceiling, "Shrinking log query range");
+        metrics::set_log_chunk(&self.chain, state.size);
+        true
+    }
+}
@@ -0,0 +1,17 @@
+use tracing_subscriber::EnvFilter;
+
+/// Installs the global tracing subscriber.
+///
+/// `RUST_LOG` selects the filter (default `info`), `LOG_FORMAT=json` switches
+/// from human-readable lines to one JSON object per event including the
+/// current route/task span fields.
+pub fn init() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    match std::env::var("LOG_FORMAT").as_deref() {
+        Ok("json") => builder.json().with_current_span(true).with_span_list(true).init(),
+        Ok("human") | Err(_) => builder.init(),
+        Ok(other) => panic!("LOG_FORMAT must be 'human' or 'json', got '{}'", other),
+    }
+}
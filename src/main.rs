@@ -1,79 +1,191 @@
+use clap::Parser;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Mutex};
-use futures_util::future::select_all;
+use std::time::Duration;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn, Instrument};
 use vea_validator::{
+    admin::{self, AdminState},
+    alerts::{self, Alerter},
+    cli::{Cli, Command},
     epoch_watcher::EpochWatcher,
+    logging,
+    metrics,
     indexer::EventIndexer,
     tasks::dispatcher::TaskDispatcher,
-    tasks::{TaskStore, ClaimStore},
+    tasks::{ClaimStore, TaskStore},
+    tasks::nonce::journal_path,
+    tasks::storage::open_route_stores,
     contracts::IVeaInboxArbToEth,
     config::{ValidatorConfig, Route},
-    startup::{check_rpc_health, check_balances, load_route_settings},
+    startup::{check_rpc_health, check_signer, check_balances, load_route_settings},
+    supervisor::supervise,
+    sync::LockExt,
 };
 
-async fn run_route(config: ValidatorConfig, route: Route, epoch_period: u64) {
-    let name = route.name.to_lowercase().replace("_", "-");
-    let schedule_path = format!("data/schedules/{}.json", name);
-    let claims_path = format!("data/claims/{}.json", name);
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(120);
 
-    println!("[{}] Inbox: {:?}, Outbox: {:?}", route.name, route.inbox_address, route.outbox_address);
+async fn shutdown_signal() {
+    let mut sigterm = signal(SignalKind::terminate()).expect("Failed to install SIGTERM handler");
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => info!("SIGINT received"),
+        _ = sigterm.recv() => info!("SIGTERM received"),
+    }
+}
 
-    let task_store = Arc::new(Mutex::new(TaskStore::new(&schedule_path)));
-    let claim_store = Arc::new(Mutex::new(ClaimStore::new(&claims_path)));
+async fn run_route(
+    config: ValidatorConfig,
+    route: Route,
+    epoch_period: u64,
+    make_claims: Arc<AtomicBool>,
+    task_store: Arc<Mutex<TaskStore>>,
+    claim_store: Arc<Mutex<ClaimStore>>,
+    shutdown: CancellationToken,
+) -> Result<(), String> {
+    info!(inbox = %route.inbox_address, outbox = %route.outbox_address, "Starting route");
+    let policy = config.supervisor;
 
-    let wallet_address = config.wallet.default_signer().address();
-    let watcher = EpochWatcher::new(route.clone(), config.make_claims, claim_store.clone(), task_store.clone());
-    let indexer = EventIndexer::new(route.clone(), wallet_address, task_store.clone(), claim_store.clone());
-    let dispatcher = TaskDispatcher::new(config, route.clone(), task_store.clone(), claim_store.clone());
+    let wallet_address = config.address();
+    let watcher = Arc::new(EpochWatcher::with_make_claims_flag(route.clone(), make_claims, claim_store.clone(), task_store.clone())
+        .with_shutdown(shutdown.clone()));
+    let indexer = Arc::new(EventIndexer::new(route.clone(), wallet_address, task_store.clone(), claim_store.clone())
+        .with_piggyback_relays(config.piggyback_relays)
+        .with_shutdown(shutdown.clone()));
+    let dispatcher = Arc::new(TaskDispatcher::new(config, route.clone(), task_store.clone(), claim_store.clone())
+        .with_shutdown(shutdown.clone()));
 
-    indexer.initialize().await;
+    // Nothing may execute on a stale sync flag while the indexer (re)initializes.
+    task_store.locked().set_on_sync(false);
 
-    tokio::select! {
-        r = watcher.watch_epochs(epoch_period) => {
-            panic!("[{}] Epoch watcher died: {:?}", route.name, r);
-        }
-        _ = indexer.run() => {
-            panic!("[{}] Indexer died unexpectedly", route.name);
-        }
-        _ = dispatcher.run() => {
-            panic!("[{}] Dispatcher died unexpectedly", route.name);
-        }
-    }
+    let (watcher_result, indexer_result, dispatcher_result) = tokio::join!(
+        supervise("epoch_watcher", policy, shutdown.clone(), || {
+            let watcher = watcher.clone();
+            async move {
+                if let Err(e) = watcher.watch_epochs(epoch_period).await {
+                    error!(error = %e, "Epoch watcher failed");
+                }
+            }
+        }),
+        supervise("indexer", policy, shutdown.clone(), || {
+            let indexer = indexer.clone();
+            async move {
+                if let Err(e) = indexer.initialize().await {
+                    error!(error = %e, "Indexer failed to initialize");
+                    return;
+                }
+                indexer.run().await;
+            }
+        }),
+        supervise("dispatcher", policy, shutdown.clone(), || {
+            let dispatcher = dispatcher.clone();
+            async move { dispatcher.run().await }
+        }),
+    );
+    info!("Route stopped");
+    watcher_result.and(indexer_result).and(dispatcher_result)
+        .map_err(|e| format!("[{}] {}", route.name, e))
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let cli = Cli::parse();
+    logging::init();
+    match cli.command {
+        None | Some(Command::Run) => run(cli.data_dir).await,
+        Some(command) => vea_validator::cli::execute(command, cli.data_dir).await,
+    }
+}
+
+async fn run(data_dir: PathBuf) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let c = ValidatorConfig::from_env()?;
-    println!("Validator wallet address: {}", c.wallet.default_signer().address());
+    info!(wallet = %c.address(), "Validator wallet address");
+    if c.dry_run {
+        warn!("Dry run: transactions are logged and recorded, never sent");
+    }
+    alerts::init(Alerter::new(&c.alerts));
+
+    if let Some(addr) = c.metrics_addr {
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(addr).await {
+                error!(error = %e, "Metrics server stopped");
+            }
+        });
+    }
 
     let mut routes = c.build_routes();
+    for route in &routes {
+        route.inbox_nonces.persist_to(journal_path(&data_dir, route.inbox_chain_id));
+        route.outbox_nonces.persist_to(journal_path(&data_dir, route.outbox_chain_id));
+    }
     check_rpc_health(&routes).await?;
+    check_signer(&c).await?;
     check_balances(&c, &routes).await?;
 
-    let eth_provider = routes[0].outbox_provider.clone();
+    let arb_outbox_provider = c.build_provider(c.arb_outbox_chain_id);
     for route in routes.iter_mut() {
-        route.settings = load_route_settings(route, c.arb_outbox, &eth_provider).await;
+        route.settings = load_route_settings(route, c.arb_outbox, &arb_outbox_provider).await;
     }
 
     let inbox = IVeaInboxArbToEth::new(routes[0].inbox_address, routes[0].inbox_provider.clone());
     let epoch_period: u64 = inbox.epochPeriod().call().await?.try_into()?;
 
-    println!("Starting validator for {} routes...", routes.len());
+    info!(routes = routes.len(), "Starting validator...");
 
-    let handles: Vec<_> = routes.into_iter()
-        .map(|route| {
-            let config = c.clone();
-            tokio::spawn(run_route(config, route, epoch_period))
-        })
-        .collect();
+    let make_claims = Arc::new(AtomicBool::new(c.make_claims));
+    let mut admin_state = AdminState::new(make_claims.clone());
 
-    tokio::select! {
-        _ = select_all(handles) => {
-            panic!("A route handler died unexpectedly");
+    let shutdown = CancellationToken::new();
+    let mut handles = JoinSet::new();
+    for route in routes {
+        let (task_store, claim_store) = open_route_stores(c.storage, &data_dir, &route.name);
+        let task_store = Arc::new(Mutex::new(task_store));
+        let claim_store = Arc::new(Mutex::new(claim_store));
+        admin_state.add_route(&route.name, task_store.clone(), claim_store.clone());
+
+        let config = c.clone();
+        let span = tracing::info_span!("route", route = %route.name);
+        handles.spawn(run_route(config, route, epoch_period, make_claims.clone(), task_store, claim_store, shutdown.clone()).instrument(span));
+    }
+
+    if let Some(addr) = c.admin_addr {
+        let admin_state = Arc::new(admin_state);
+        tokio::spawn(async move {
+            if let Err(e) = admin::serve(addr, admin_state).await {
+                error!(error = %e, "Admin server stopped");
+            }
+        });
+    }
+
+    let escalation = tokio::select! {
+        Some(r) = handles.join_next() => {
+            let reason = match r {
+                Ok(Err(e)) => e,
+                Ok(Ok(())) => "Route stopped unexpectedly".to_string(),
+                Err(e) => format!("Route task failed: {}", e),
+            };
+            error!(reason = %reason, "Escalating to process exit");
+            Some(reason)
         }
-        _ = tokio::signal::ctrl_c() => {
-            println!("\nShutting down...");
+        _ = shutdown_signal() => {
+            info!("Shutting down, draining in-flight tasks...");
+            None
         }
+    };
+
+    shutdown.cancel();
+    let drained = tokio::time::timeout(DRAIN_TIMEOUT, async {
+        while handles.join_next().await.is_some() {}
+    }).await;
+    match drained {
+        Ok(()) => info!("All routes stopped, state persisted"),
+        Err(_) => warn!(timeout_secs = DRAIN_TIMEOUT.as_secs(), "Drain timed out; unfinished tasks stay queued and rerun on restart"),
     }
 
-    Ok(())
+    match escalation {
+        Some(reason) => Err(reason.into()),
+        None => Ok(()),
+    }
 }
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::{LazyLock, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use crate::sync::LockExt;
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::default);
+
+struct Family {
+    help: &'static str,
+    kind: &'static str,
+    series: BTreeMap<String, f64>,
+}
+
+/// Minimal Prometheus registry: gauges and counters keyed by name and labels,
+/// rendered in the text exposition format.
+#[derive(Default)]
+pub struct Metrics {
+    families: Mutex<BTreeMap<&'static str, Family>>,
+}
+
+fn label_string(labels: &[(&str, &str)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let pairs: Vec<String> = labels.iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, v.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect();
+    format!("{{{}}}", pairs.join(","))
+}
+
+impl Metrics {
+    fn with_series(&self, name: &'static str, help: &'static str, kind: &'static str, labels: &[(&str, &str)], f: impl FnOnce(&mut f64)) {
+        let mut families = self.families.locked();
+        let family = families.entry(name).or_insert_with(|| Family { help, kind, series: BTreeMap::new() });
+        f(family.series.entry(label_string(labels)).or_insert(0.0));
+    }
+
+    pub fn set_gauge(&self, name: &'static str, help: &'static str, labels: &[(&str, &str)], value: f64) {
+        self.with_series(name, help, "gauge", labels, |v| *v = value);
+    }
+
+    pub fn inc_counter(&self, name: &'static str, help: &'static str, labels: &[(&str, &str)], by: f64) {
+        self.with_series(name, help, "counter", labels, |v| *v += by);
+    }
+
+    pub fn get(&self, name: &str, labels: &[(&str, &str)]) -> Option<f64> {
+        let families = self.families.locked();
+        families.get(name)?.series.get(&label_string(labels)).copied()
+    }
+
+    pub fn render(&self) -> String {
+        let families = self.families.locked();
+        let mut out = String::new();
+        for (name, family) in families.iter() {
+            out.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n", name, family.help, name, family.kind));
+            for (labels, value) in &family.series {
+                out.push_str(&format!("{}{} {}\n", name, labels, value));
+            }
+        }
+        out
+    }
+}
+
+pub fn set_indexer_block(route: &str, chain: &str, block: u64) {
+    METRICS.set_gauge("vea_indexer_last_block", "Last block scanned by the indexer", &[("route", route), ("chain", chain)], block as f64);
+}
+
+pub fn record_block_time_lookup(chain: &str, result: &str) {
+    METRICS.inc_counter("vea_block_time_lookups_total", "Block timestamp lookups by cache result", &[("chain", chain), ("result", result)], 1.0);
+}
+
+pub fn set_log_chunk(chain: &str, blocks: u64) {
+    METRICS.set_gauge("vea_log_chunk_blocks", "Block range of the indexer's next eth_getLogs query", &[("chain", chain)], blocks as f64);
+}
+
+pub fn set_chain_head(route: &str, chain: &str, block: u64) {
+    METRICS.set_gauge("vea_chain_head_block", "Latest block seen on the chain", &[("route", route), ("chain", chain)], block as f64);
+}
+
+pub fn set_on_sync(route: &str, on_sync: bool) {
+    METRICS.set_gauge("vea_on_sync", "Whether the indexer has caught up (1) or is still syncing (0)", &[("route", route)], if on_sync { 1.0 } else { 0.0 });
+}
+
+pub fn set_pending_tasks(route: &str, kind: &str, count: usize) {
+    METRICS.set_gauge("vea_pending_tasks", "Scheduled tasks by kind", &[("route", route), ("kind", kind)], count as f64);
+}
+
+pub fn set_dead_letters(route: &str, count: usize) {
+    METRICS.set_gauge("vea_dead_letters", "Tasks that exhausted their retry budget and await an operator", &[("route", route)], count as f64);
+}
+
+pub fn set_deadline_tasks(route: &str, urgency: &str, count: usize) {
+    METRICS.set_gauge("vea_deadline_tasks", "Queued tasks with a deadline by urgency", &[("route", route), ("urgency", urgency)], count as f64);
+}
+
+pub fn record_missed_deadline(route: &str, kind: &str) {
+    METRICS.inc_counter("vea_missed_deadlines_total", "Tasks still queued when their deadline passed", &[("route", route), ("kind", kind)], 1.0);
+}
+
+pub fn record_claim_skipped(route: &str, reason: &str) {
+    METRICS.inc_counter("vea_claims_skipped_total", "Epochs left unclaimed by the claim policy", &[("route", route), ("reason", reason)], 1.0);
+}
+
+pub fn record_dry_run_tx(route: &str, action: &str) {
+    METRICS.inc_counter("vea_dry_run_transactions_total", "Transactions recorded instead of sent in dry-run mode", &[("route", route), ("action", action)], 1.0);
+}
+
+pub fn record_alert(kind: &str, status: &str) {
+    METRICS.inc_counter("vea_alerts_total", "Alerts sent or suppressed as duplicates", &[("kind", kind), ("status", status)], 1.0);
+}
+
+pub fn record_reorg(route: &str, chain: &str) {
+    METRICS.inc_counter("vea_reorgs_total", "Reorgs that rolled back the indexer", &[("route", route), ("chain", chain)], 1.0);
+}
+
+pub fn record_tx_replacement(chain: &str, action: &str) {
+    METRICS.inc_counter("vea_tx_replacements_total", "Stuck transactions re-sent with a higher fee", &[("chain", chain), ("action", action)], 1.0);
+}
+
+pub fn set_claims(route: &str, honest: &str, count: usize) {
+    METRICS.set_gauge("vea_claims", "Tracked claims by honest party", &[("route", route), ("honest", honest)], count as f64);
+}
+
+pub fn record_task(route: &str, kind: &str, result: &str) {
+    METRICS.inc_counter("vea_task_executions_total", "Task executions by result (success, deferred or TaskError variant)", &[("route", route), ("kind", kind), ("result", result)], 1.0);
+}
+
+pub fn set_wallet_balance(route: &str, chain: &str, currency: &str, wei: alloy::primitives::U256) {
+    METRICS.set_gauge("vea_wallet_balance_wei", "Validator wallet balance", &[("route", route), ("chain", chain), ("currency", currency)], f64::from(wei));
+}
+
+pub fn record_tx(route: &str, action: &str, gas_used: u64, gas_price: u128, success: bool) {
+    let status = if success { "success" } else { "reverted" };
+    METRICS.inc_counter("vea_transactions_total", "Transactions mined by action and status", &[("route", route), ("action", action), ("status", status)], 1.0);
+    METRICS.inc_counter("vea_gas_used_total", "Gas used by mined transactions", &[("route", route), ("action", action)], gas_used as f64);
+    METRICS.inc_counter("vea_gas_spent_wei_total", "Fees paid by mined transactions", &[("route", route), ("action", action)], gas_used as f64 * gas_price as f64);
+}
+
+/// Serves `GET /metrics` on `addr` until the listener fails.
+pub async fn serve(addr: SocketAddr) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    tracing::info!(addr = %listener.local_addr()?, "Serving /metrics");
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1024];
+            let n = match stream.read(&mut buf).await {
+                Ok(n) => n,
+                Err(_) => return,
+            };
+            let request = String::from_utf8_lossy(&buf[..n]);
+            let response = if request.starts_with("GET /metrics ") {
+                let body = METRICS.render();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(), body
+                )
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+            };
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
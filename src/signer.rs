@@ -0,0 +1,136 @@
+use alloy::consensus::{SignableTransaction, TxEnvelope};
+use alloy::eips::eip2718::Decodable2718;
+use alloy::network::{EthereumWallet, TxSigner};
+use alloy::primitives::{Address, Bytes, Signature};
+use alloy::rpc::types::{TransactionInput, TransactionRequest};
+use alloy::signers::local::PrivateKeySigner;
+use alloy::transports::http::reqwest;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+const KEY_SECRET: &str = "/run/secrets/validator_key";
+
+/// Where the validator's key lives.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignerConfig {
+    /// Raw hex key from `PRIVATE_KEY` or `/run/secrets/validator_key`.
+    #[default]
+    Key,
+    /// Encrypted JSON keystore, unlocked with the password in `password_file`.
+    Keystore { path: PathBuf, password_file: PathBuf },
+    /// External signer (Web3Signer, Clef) that holds the key for `address`
+    /// and signs over `eth_signTransaction`.
+    Remote { url: String, address: Address },
+}
+
+impl SignerConfig {
+    /// The wallet to sign with, or `None` if `Key` is selected but no key is
+    /// provided.
+    pub fn load(&self) -> Result<Option<EthereumWallet>, Box<dyn std::error::Error + Send + Sync>> {
+        match self {
+            SignerConfig::Key => {
+                let key = std::env::var("PRIVATE_KEY")
+                    .or_else(|_| std::fs::read_to_string(KEY_SECRET).map(|s| s.trim().to_string()));
+                match key {
+                    Ok(key) => Ok(Some(EthereumWallet::from(PrivateKeySigner::from_str(&key)?))),
+                    Err(_) => Ok(None),
+                }
+            }
+            SignerConfig::Keystore { path, password_file } => {
+                let password = std::fs::read_to_string(password_file)
+                    .map_err(|e| format!("Cannot read keystore password file {}: {}", password_file.display(), e))?;
+                let signer = PrivateKeySigner::decrypt_keystore(path, password.trim_end_matches(['\r', '\n']))
+                    .map_err(|e| format!("Cannot decrypt keystore {}: {}", path.display(), e))?;
+                Ok(Some(EthereumWallet::from(signer)))
+            }
+            SignerConfig::Remote { url, address } => Ok(Some(EthereumWallet::from(RemoteSigner::new(url, *address)))),
+        }
+    }
+}
+
+/// Signs through an external signer's JSON-RPC, so the key never enters this
+/// process. The returned transaction is checked against the one requested
+/// before its signature is used.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    url: String,
+    address: Address,
+    client: reqwest::Client,
+}
+
+impl RemoteSigner {
+    pub fn new(url: &str, address: Address) -> Self {
+        Self { url: url.to_string(), address, client: reqwest::Client::new() }
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let response: Value = self.client.post(&self.url)
+            .json(&json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params}))
+            .send().await?
+            .error_for_status()?
+            .json().await?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("{} failed: {}", method, error).into());
+        }
+        response.get("result").cloned().ok_or_else(|| format!("{} returned no result", method).into())
+    }
+
+    /// Accounts the signer holds keys for.
+    pub async fn accounts(&self) -> Result<Vec<Address>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(serde_json::from_value(self.call("eth_accounts", json!([])).await?)?)
+    }
+
+    fn request(&self, tx: &dyn SignableTransaction<Signature>) -> TransactionRequest {
+        let mut request = TransactionRequest {
+            from: Some(self.address),
+            to: Some(tx.kind()),
+            nonce: Some(tx.nonce()),
+            gas: Some(tx.gas_limit()),
+            value: Some(tx.value()),
+            input: TransactionInput::both(tx.input().clone()),
+            chain_id: tx.chain_id(),
+            transaction_type: Some(tx.ty()),
+            access_list: tx.access_list().cloned(),
+            ..Default::default()
+        };
+        if tx.is_dynamic_fee() {
+            request.max_fee_per_gas = Some(tx.max_fee_per_gas());
+            request.max_priority_fee_per_gas = tx.max_priority_fee_per_gas();
+        } else {
+            request.gas_price = tx.gas_price();
+        }
+        request
+    }
+
+    async fn sign(&self, tx: &dyn SignableTransaction<Signature>) -> Result<Signature, Box<dyn std::error::Error + Send + Sync>> {
+        // Web3Signer returns the raw transaction, Clef and geth wrap it as `{raw, tx}`.
+        let result = self.call("eth_signTransaction", json!([self.request(tx)])).await?;
+        let raw: Bytes = serde_json::from_value(result.get("raw").cloned().unwrap_or(result))?;
+        let signed = TxEnvelope::decode_2718(&mut raw.as_ref())?;
+
+        let hash = tx.signature_hash();
+        if signed.signature_hash() != hash {
+            return Err("remote signer signed a different transaction".into());
+        }
+        let signature = *signed.signature();
+        if signature.recover_address_from_prehash(&hash)? != self.address {
+            return Err(format!("remote signer did not sign as {}", self.address).into());
+        }
+        Ok(signature)
+    }
+}
+
+#[async_trait]
+impl TxSigner<Signature> for RemoteSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_transaction(&self, tx: &mut dyn SignableTransaction<Signature>) -> alloy::signers::Result<Signature> {
+        self.sign(tx).await.map_err(alloy::signers::Error::other)
+    }
+}
@@ -1,88 +1,107 @@
 use alloy::primitives::{Address, U256};
 use alloy::providers::{Provider, DynProvider};
 use alloy::network::Ethereum;
-use crate::contracts::{IVeaOutbox, IWETH, IOutbox, IRollup};
+use crate::contracts::{IVeaOutbox, IOutbox, IRollup};
+use crate::alerts::{self, Alert, AlertKind};
 use crate::config::{ValidatorConfig, Route, RouteSettings};
+use crate::metrics;
+use crate::signer::{RemoteSigner, SignerConfig};
+use tracing::{info, warn};
 
 const TIMING_SAFETY_BUFFER_SECS: u64 = 10 * 60;
 
 pub async fn check_rpc_health(routes: &[Route]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    println!("Checking RPC endpoint health...");
-
-    let arb_provider = &routes[0].inbox_provider;
-    let eth_provider = &routes[0].outbox_provider;
-    let gnosis_provider = &routes[1].outbox_provider;
-
-    let arb_block = arb_provider.get_block_number().await
-        .map_err(|e| panic!("FATAL: Arbitrum RPC unreachable or unhealthy: {}", e))?;
-    println!("✓ Arbitrum RPC healthy (block: {})", arb_block);
-    let eth_block = eth_provider.get_block_number().await
-        .map_err(|e| panic!("FATAL: Ethereum RPC unreachable or unhealthy: {}", e))?;
-    println!("✓ Ethereum RPC healthy (block: {})", eth_block);
-    let gnosis_block = gnosis_provider.get_block_number().await
-        .map_err(|e| panic!("FATAL: Gnosis RPC unreachable or unhealthy: {}", e))?;
-    println!("✓ Gnosis RPC healthy (block: {})", gnosis_block);
+    info!("Checking RPC endpoint health...");
+
+    let mut checked = Vec::new();
+    for route in routes {
+        let endpoints = [
+            (route.inbox_chain_id, &route.inbox_chain_name, &route.inbox_provider),
+            (route.outbox_chain_id, &route.outbox_chain_name, &route.outbox_provider),
+        ];
+        for (chain_id, chain_name, provider) in endpoints {
+            if checked.contains(&chain_id) {
+                continue;
+            }
+            let block = provider.get_block_number().await
+                .map_err(|e| panic!("FATAL: {} RPC unreachable or unhealthy: {}", chain_name, e))?;
+            info!(chain = %chain_name, block, "RPC healthy");
+            checked.push(chain_id);
+        }
+    }
     Ok(())
 }
 
-pub async fn check_balances(c: &ValidatorConfig, routes: &[Route]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let wallet_address = c.wallet.default_signer().address();
-    let eth_provider = routes[0].outbox_provider.clone();
-    let gnosis_provider = routes[1].outbox_provider.clone();
+/// A remote signer that is down or lacks the key would fail every transaction.
+pub async fn check_signer(c: &ValidatorConfig) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let SignerConfig::Remote { url, address } = &c.signer else {
+        return Ok(());
+    };
+    let accounts = RemoteSigner::new(url, *address).accounts().await
+        .map_err(|e| panic!("FATAL: Remote signer {} unreachable: {}", url, e))?;
+    if !accounts.contains(address) {
+        panic!("FATAL: Remote signer {} holds no key for {}", url, address);
+    }
+    info!(url = %url, address = %address, "Remote signer ready");
+    Ok(())
+}
 
-    let eth_outbox = IVeaOutbox::new(c.outbox_arb_to_eth, eth_provider.clone());
-    let gnosis_outbox = IVeaOutbox::new(c.outbox_arb_to_gnosis, gnosis_provider.clone());
+/// Reads each route's deposit and gas balances without sending anything and
+/// returns the shortfalls.
+pub async fn inspect_balances(
+    wallet_address: Address,
+    routes: &[Route],
+) -> Result<Vec<(&Route, String)>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut shortfalls = Vec::new();
+    for route in routes {
+        let outbox = IVeaOutbox::new(route.outbox_address, route.outbox_provider.clone());
+        let deposit = outbox.deposit().call().await?;
+        let balance = route.adapter.deposit_balance(route, wallet_address).await?;
+        let currency = route.adapter.deposit_currency(route);
+        metrics::set_wallet_balance(&route.name, &route.outbox_chain_name, &currency, balance);
+        let mut passed = true;
+        if balance < deposit {
+            shortfalls.push((route, format!("Insufficient {} balance on {}. Need {} wei for deposit, have {} wei", currency, route.outbox_chain_name, deposit, balance)));
+            passed = false;
+        }
 
-    let eth_deposit = eth_outbox.deposit().call().await?;
-    let eth_balance = eth_provider.get_balance(wallet_address).await?;
-    if eth_balance < eth_deposit {
-        panic!("FATAL: Insufficient ETH balance. Need {} wei for deposit, have {} wei", eth_deposit, eth_balance);
+        if let Some(native_balance) = route.adapter.gas_balance(route, wallet_address).await? {
+            let native = &route.outbox_native_currency;
+            metrics::set_wallet_balance(&route.name, &route.outbox_chain_name, native, native_balance);
+            let native_min = U256::from(10_000_000_000_000_000u64);
+            if native_balance < native_min {
+                shortfalls.push((route, format!("Insufficient {} on {} for gas. Need {} wei, have {} wei", native, route.outbox_chain_name, native_min, native_balance)));
+                passed = false;
+            }
+        }
+        if passed {
+            info!(route = %route.name, currency = %currency, balance = %balance, "Balance check passed");
+        }
     }
+    Ok(shortfalls)
+}
 
-    let gnosis_deposit = gnosis_outbox.deposit().call().await?;
-    let weth_addr = c.chains.get(&100).expect("Gnosis").deposit_token
-        .expect("Gnosis should use WETH");
-    let weth = IWETH::new(weth_addr, gnosis_provider.clone());
-    let weth_balance = weth.balanceOf(wallet_address).call().await?;
-    if weth_balance < gnosis_deposit {
-        panic!("FATAL: Insufficient WETH balance on Gnosis. Need {} wei for deposit, have {} wei", gnosis_deposit, weth_balance);
+/// Startup gate for `run`: fails on a shortfall (warns in dry-run mode) and
+/// prepares each route for deposits, which may send an approval.
+pub async fn check_balances(c: &ValidatorConfig, routes: &[Route]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    for (route, msg) in inspect_balances(c.address(), routes).await? {
+        insufficient(c, route, msg).await;
     }
-
-    let xdai_balance = gnosis_provider.get_balance(wallet_address).await?;
-    let xdai_min = U256::from(10_000_000_000_000_000u64);
-    if xdai_balance < xdai_min {
-        panic!("FATAL: Insufficient xDAI on Gnosis for gas. Need {} wei, have {} wei", xdai_min, xdai_balance);
+    for route in routes {
+        route.adapter.prepare_deposit(route).await
+            .unwrap_or_else(|e| panic!("FATAL: [{}] preparing deposits failed: {}", route.name, e));
     }
-    println!("✓ Balance check passed: ETH={} wei, WETH={} wei, xDAI={} wei", eth_balance, weth_balance, xdai_balance);
-
-    ensure_weth_approval(c, gnosis_provider, wallet_address).await?;
-
     Ok(())
 }
 
-pub async fn ensure_weth_approval(c: &ValidatorConfig, gnosis_provider: DynProvider<Ethereum>, wallet_address: alloy::primitives::Address) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let weth_addr = c.chains.get(&100).expect("Gnosis").deposit_token
-        .expect("Gnosis should use WETH");
-    let weth = IWETH::new(weth_addr, gnosis_provider);
-    let current_allowance = weth.allowance(wallet_address, c.outbox_arb_to_gnosis).call().await?;
-
-    if current_allowance == U256::ZERO {
-        println!("⚠️  No WETH approval found for Gnosis outbox. Setting max approval...");
-        let max_approval = U256::MAX;
-        let approve_tx = weth.approve(c.outbox_arb_to_gnosis, max_approval);
-        let pending = approve_tx.send().await?;
-        let receipt = pending.get_receipt().await?;
-
-        if !receipt.status() {
-            panic!("FATAL: WETH approval transaction failed");
-        }
-
-        println!("✓ WETH max approval set for Gnosis outbox");
+/// A watch-only dry run may shadow an address it cannot fund.
+async fn insufficient(c: &ValidatorConfig, route: &Route, msg: String) {
+    alerts::notify(Alert::new(AlertKind::LowBalance, &route.name, &route.outbox_chain_name, &msg)).await;
+    if c.dry_run {
+        warn!("Dry run: {}", msg);
     } else {
-        println!("✓ WETH approval already exists: {} wei", current_allowance);
+        panic!("FATAL: {}", msg);
     }
-
-    Ok(())
 }
 
 async fn get_avg_block_time_ms(provider: &DynProvider<Ethereum>) -> u64 {
@@ -104,10 +123,10 @@ pub async fn load_route_settings(
     arb_outbox_address: Address,
     arb_outbox_provider: &DynProvider<Ethereum>,
 ) -> RouteSettings {
-    println!("[{}] Loading route settings from contracts...", route.name);
+    info!(route = %route.name, "Loading route settings from contracts...");
 
     let avg_block_time_ms = get_avg_block_time_ms(arb_outbox_provider).await;
-    println!("[{}] Average block time: {}ms", route.name, avg_block_time_ms);
+    info!(route = %route.name, avg_block_time_ms, "Average block time");
 
     let arb_outbox = IOutbox::new(arb_outbox_address, arb_outbox_provider.clone());
     let rollup_address = arb_outbox.rollup().call().await
@@ -115,27 +134,26 @@ pub async fn load_route_settings(
     let rollup = IRollup::new(rollup_address, arb_outbox_provider.clone());
     let confirm_period_blocks: u64 = rollup.confirmPeriodBlocks().call().await
         .expect("Failed to get confirmPeriodBlocks");
-    println!("[{}] Rollup confirmPeriodBlocks: {}", route.name, confirm_period_blocks);
+    info!(route = %route.name, confirm_period_blocks, "Rollup confirmPeriodBlocks");
 
     let outbox = IVeaOutbox::new(route.outbox_address, route.outbox_provider.clone());
     let sequencer_delay_limit = outbox.sequencerDelayLimit().call().await.expect("Failed to get sequencerDelayLimit").to::<u64>();
     let min_challenge_period = outbox.minChallengePeriod().call().await.expect("Failed to get minChallengePeriod").to::<u64>();
     let epoch_period = outbox.epochPeriod().call().await.expect("Failed to get epochPeriod").to::<u64>();
-    println!("[{}] Outbox params: sequencerDelayLimit={}s, epochPeriod={}s, minChallengePeriod={}s",
-        route.name, sequencer_delay_limit, epoch_period, min_challenge_period);
+    info!(route = %route.name, sequencer_delay_limit, epoch_period, min_challenge_period, "Outbox params");
 
     let relay_delay_secs = (confirm_period_blocks * avg_block_time_ms / 1000) + TIMING_SAFETY_BUFFER_SECS;
     let start_verification_delay = sequencer_delay_limit + epoch_period + TIMING_SAFETY_BUFFER_SECS;
     let min_challenge_period_with_buffer = min_challenge_period + TIMING_SAFETY_BUFFER_SECS;
     let sync_lookback_secs = relay_delay_secs + start_verification_delay + min_challenge_period_with_buffer + TIMING_SAFETY_BUFFER_SECS;
 
-    println!("[{}] Computed: relay_delay={}s, start_verification_delay={}s, min_challenge_period={}s, sync_lookback={}s",
-        route.name, relay_delay_secs, start_verification_delay, min_challenge_period_with_buffer, sync_lookback_secs);
+    info!(route = %route.name, relay_delay_secs, start_verification_delay, min_challenge_period = min_challenge_period_with_buffer, sync_lookback_secs, "Computed route settings");
 
     RouteSettings {
         relay_delay_secs,
         start_verification_delay,
         min_challenge_period: min_challenge_period_with_buffer,
         sync_lookback_secs,
+        challenge_window_secs: sequencer_delay_limit + epoch_period + min_challenge_period,
     }
 }
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Instant;
+use tokio::time::{sleep, Duration};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn, Instrument};
+
+/// Restart policy for route components. A component that fails more than
+/// `failure_budget` times within `budget_window_secs` escalates to process exit.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SupervisorConfig {
+    pub initial_backoff_secs: u64,
+    pub max_backoff_secs: u64,
+    pub failure_budget: u32,
+    pub budget_window_secs: u64,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff_secs: 5,
+            max_backoff_secs: 5 * 60,
+            failure_budget: 10,
+            budget_window_secs: 60 * 60,
+        }
+    }
+}
+
+impl SupervisorConfig {
+    /// Backoff before the restart following the `failures`-th recent failure.
+    pub fn backoff(&self, failures: usize) -> Duration {
+        let exp = failures.saturating_sub(1).min(32) as u32;
+        let secs = self.initial_backoff_secs.saturating_mul(2u64.saturating_pow(exp));
+        Duration::from_secs(secs.min(self.max_backoff_secs))
+    }
+}
+
+/// Runs the component built by `start` on its own task, restarting it with
+/// exponential backoff whenever it panics or returns before `shutdown` is
+/// cancelled. Returns `Err` once the failure budget is exhausted.
+pub async fn supervise<F, Fut>(
+    component: &str,
+    config: SupervisorConfig,
+    shutdown: CancellationToken,
+    mut start: F,
+) -> Result<(), String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let window = Duration::from_secs(config.budget_window_secs);
+    let mut failures: Vec<Instant> = Vec::new();
+    loop {
+        let result = tokio::spawn(start().in_current_span()).await;
+        if shutdown.is_cancelled() {
+            return Ok(());
+        }
+        match result {
+            Ok(()) => warn!(component, "Component exited unexpectedly"),
+            Err(e) => error!(component, error = %e, "Component panicked"),
+        }
+
+        let now = Instant::now();
+        failures.retain(|t| now.duration_since(*t) < window);
+        failures.push(now);
+        if failures.len() > config.failure_budget as usize {
+            error!(component, failures = failures.len(), "Failure budget exhausted");
+            return Err(format!("{} failed {} times within {}s", component, failures.len(), config.budget_window_secs));
+        }
+
+        let backoff = config.backoff(failures.len());
+        info!(component, restart_in_secs = backoff.as_secs(), failures = failures.len(), "Restarting component");
+        tokio::select! {
+            _ = sleep(backoff) => {}
+            _ = shutdown.cancelled() => return Ok(()),
+        }
+    }
+}
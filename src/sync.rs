@@ -0,0 +1,14 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+pub trait LockExt<T> {
+    /// Locks the mutex even if a previous holder panicked, so a supervised
+    /// component can restart. Store backends apply a batch all-or-nothing,
+    /// so a panic mid-update leaves nothing half-written behind the lock.
+    fn locked(&self) -> MutexGuard<'_, T>;
+}
+
+impl<T> LockExt<T> for Mutex<T> {
+    fn locked(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -1,62 +1,40 @@
-use alloy::primitives::U256;
-use alloy::providers::Provider;
 use std::sync::{Arc, Mutex};
 use crate::config::{Route, ValidatorConfig};
-use crate::contracts::{IVeaOutboxArbToEth, IVeaOutboxArbToGnosis, IWETH};
-use crate::tasks::{send_tx, was_event_emitted, ClaimStore};
+use crate::contracts::IVeaOutbox;
+use crate::tasks::fees::FeeBid;
+use crate::tasks::{was_event_emitted, ClaimStore, TaskError};
+use tracing::{info, warn};
+use crate::sync::LockExt;
 
 pub async fn execute(
     config: &ValidatorConfig,
     route: &Route,
     epoch: u64,
     claim_store: &Arc<Mutex<ClaimStore>>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let claim = claim_store.lock().unwrap().get_claim(epoch);
-    let wallet_address = config.wallet.default_signer().address();
+    bid: FeeBid,
+) -> Result<(), TaskError> {
+    let claim = claim_store.locked().get_claim(epoch);
+    let wallet_address = config.address();
 
-    let result = if let Some(weth_address) = route.weth_address {
-        let outbox = IVeaOutboxArbToGnosis::new(route.outbox_address, route.outbox_provider.clone());
-        let deposit = outbox.deposit().call().await?;
+    let outbox = IVeaOutbox::new(route.outbox_address, route.outbox_provider.clone());
+    let deposit = outbox.deposit().call().await?;
 
-        let weth = IWETH::new(weth_address, route.outbox_provider.clone());
-        let balance = weth.balanceOf(wallet_address).call().await?;
-        if balance < deposit {
-            println!("[{}][task::challenge] Insufficient WETH (have {}, need {}), will retry", route.name, balance, deposit);
-            return Err("Insufficient funds".into());
-        }
-
-        send_tx(
-            outbox.challenge(U256::from(epoch), claim).send().await,
-            "challenge",
-            route.name,
-            &["already"],
-        ).await
-    } else {
-        let outbox = IVeaOutboxArbToEth::new(route.outbox_address, route.outbox_provider.clone());
-        let deposit = outbox.deposit().call().await?;
-
-        let balance = route.outbox_provider.get_balance(wallet_address).await?;
-        if balance < deposit {
-            println!("[{}][task::challenge] Insufficient ETH (have {}, need {}), will retry", route.name, balance, deposit);
-            return Err("Insufficient funds".into());
-        }
+    let balance = route.adapter.deposit_balance(route, wallet_address).await?;
+    if balance < deposit {
+        warn!(epoch, currency = %route.adapter.deposit_currency(route), have = %balance, need = %deposit, "Insufficient funds, will retry");
+        return Err(TaskError::InsufficientFunds(format!("have {} wei, need {} wei", balance, deposit)));
+    }
 
-        send_tx(
-            outbox.challenge(U256::from(epoch), claim).value(deposit).send().await,
-            "challenge",
-            route.name,
-            &["already"],
-        ).await
-    };
+    let result = route.adapter.challenge(route, epoch, claim, deposit, bid).await;
 
     if let Err(e) = result {
         if was_event_emitted(&route.outbox_provider, route.outbox_address, "Challenged(uint256,address)", epoch).await {
-            println!("[{}][task::challenge] Epoch {} already challenged by another validator", route.name, epoch);
-            return Ok(());
+            info!(epoch, "Already challenged by another validator");
+            return Err(TaskError::RaceLost);
         }
         if was_event_emitted(&route.outbox_provider, route.outbox_address, "VerificationStarted(uint256)", epoch).await {
-            println!("[{}][task::challenge] Epoch {} verification started, claimHash changed - will retry", route.name, epoch);
-            return Err("VerificationStarted".into());
+            warn!(epoch, "Verification started, claimHash changed - will retry");
+            return Err(TaskError::Retryable { delay: 15 * 60, reason: "verification started, claimHash changed".to_string() });
         }
         return Err(e);
     }
@@ -1,8 +1,14 @@
 use alloy::primitives::{FixedBytes, U256};
+use alloy::providers::Provider;
 use std::sync::{Arc, Mutex};
 use crate::config::Route;
-use crate::contracts::{IVeaInbox, IVeaOutbox, IVeaOutboxArbToEth, IVeaOutboxArbToGnosis};
-use crate::tasks::{send_tx, ClaimStore};
+use crate::contracts::{IVeaInbox, IVeaOutbox};
+use crate::metrics;
+use crate::tasks::claim_policy::ClaimMode;
+use crate::tasks::fees::FeeBid;
+use crate::tasks::{ClaimStore, TaskError};
+use tracing::info;
+use crate::sync::LockExt;
 
 const SEVEN_DAYS_SECS: u32 = 7 * 24 * 3600;
 
@@ -11,50 +17,54 @@ pub async fn execute(
     epoch: u64,
     claim_store: &Arc<Mutex<ClaimStore>>,
     current_timestamp: u64,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bid: FeeBid,
+) -> Result<(), TaskError> {
     let inbox = IVeaInbox::new(route.inbox_address, route.inbox_provider.clone());
     let outbox = IVeaOutbox::new(route.outbox_address, route.outbox_provider.clone());
 
     let state_root = inbox.snapshots(U256::from(epoch)).call().await?;
     if state_root == FixedBytes::<32>::ZERO {
-        println!("[{}][task::claim] Epoch {} has no snapshot", route.name, epoch);
+        info!(epoch, "No snapshot");
         return Ok(());
     }
 
     let claim_hash = outbox.claimHashes(U256::from(epoch)).call().await?;
     if claim_hash != FixedBytes::<32>::ZERO {
-        println!("[{}][task::claim] Epoch {} already claimed", route.name, epoch);
+        info!(epoch, "Already claimed");
         return Ok(());
     }
 
     let current_state_root = outbox.stateRoot().call().await?;
     if current_state_root == state_root {
-        println!("[{}][task::claim] Epoch {} state root already verified on outbox", route.name, epoch);
+        info!(epoch, "State root already verified on outbox");
         return Ok(());
     }
 
     let since = (current_timestamp as u32).saturating_sub(SEVEN_DAYS_SECS);
-    if claim_store.lock().unwrap().has_state_root_in_recent_claims(state_root, since) {
-        println!("[{}][task::claim] Epoch {} state root already in pending claim", route.name, epoch);
+    if claim_store.locked().has_state_root_in_recent_claims(state_root, since) {
+        info!(epoch, "State root already in pending claim");
         return Ok(());
     }
 
-    if route.weth_address.is_some() {
-        let outbox = IVeaOutboxArbToGnosis::new(route.outbox_address, route.outbox_provider.clone());
-        send_tx(
-            outbox.claim(U256::from(epoch), state_root).send().await,
-            "claim",
-            route.name,
-            &["already"],
-        ).await
-    } else {
-        let outbox = IVeaOutboxArbToEth::new(route.outbox_address, route.outbox_provider.clone());
-        let deposit = outbox.deposit().call().await?;
-        send_tx(
-            outbox.claim(U256::from(epoch), state_root).value(deposit).send().await,
-            "claim",
-            route.name,
-            &["already"],
-        ).await
+    let deposit = outbox.deposit().call().await?;
+    if route.claim_policy.mode == ClaimMode::IfProfitable {
+        let claim_gas = route.adapter.estimate_claim_gas(route, epoch, state_root, deposit).await?;
+        let (fee_per_gas, _) = bid.price(route.outbox_provider.estimate_eip1559_fees().await?);
+        let cost = route.claim_policy.cost(claim_gas, fee_per_gas);
+        if !route.claim_policy.is_profitable(cost) {
+            info!(epoch, cost_wei = cost, reward_wei = route.claim_policy.reward_wei, "Claim not profitable, skipping");
+            metrics::record_claim_skipped(&route.name, "unprofitable");
+            return Ok(());
+        }
+        info!(epoch, cost_wei = cost, reward_wei = route.claim_policy.reward_wei, "Claim profitable");
     }
+
+    let result = route.adapter.claim(route, epoch, state_root, deposit, bid).await;
+    if let Err(TaskError::Reverted { .. }) = &result
+        && outbox.claimHashes(U256::from(epoch)).call().await? != FixedBytes::<32>::ZERO
+    {
+        info!(epoch, "Already claimed by another validator");
+        return Err(TaskError::RaceLost);
+    }
+    result
 }
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimMode {
+    /// Claim every epoch with a snapshot nobody has claimed.
+    Always,
+    /// Claim only when `reward_wei` covers the estimated gas.
+    IfProfitable,
+    /// Leave each epoch to other validators for `wait_secs` first.
+    IfUnclaimed,
+}
+
+/// When the epoch watcher makes claims. An honest claim gets its deposit
+/// back, so the cost is the gas for claim, startVerification, verifySnapshot
+/// and withdrawClaimDeposit on the outbox chain.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClaimPolicy {
+    pub mode: ClaimMode,
+    /// Paid to the claimer per verified claim (wei), e.g. a subsidy.
+    pub reward_wei: u128,
+    /// Margin the reward must leave after costs (wei).
+    pub min_profit_wei: u128,
+    pub wait_secs: u64,
+    /// Gas of the follow-up transactions, which cannot be estimated until the
+    /// claim exists.
+    pub start_verification_gas: u64,
+    pub verify_snapshot_gas: u64,
+    pub withdraw_gas: u64,
+}
+
+impl Default for ClaimPolicy {
+    fn default() -> Self {
+        Self {
+            mode: ClaimMode::Always,
+            reward_wei: 0,
+            min_profit_wei: 0,
+            wait_secs: 30 * 60,
+            start_verification_gas: 100_000,
+            verify_snapshot_gas: 100_000,
+            withdraw_gas: 60_000,
+        }
+    }
+}
+
+impl ClaimPolicy {
+    /// Total cost of a claim's transactions at `fee_per_gas`.
+    pub fn cost(&self, claim_gas: u64, fee_per_gas: u128) -> u128 {
+        let gas = claim_gas + self.start_verification_gas + self.verify_snapshot_gas + self.withdraw_gas;
+        (gas as u128).saturating_mul(fee_per_gas)
+    }
+
+    pub fn is_profitable(&self, cost_wei: u128) -> bool {
+        self.reward_wei >= cost_wei.saturating_add(self.min_profit_wei)
+    }
+
+    /// Seconds after an epoch ends before this validator may claim it.
+    pub fn claim_delay(&self) -> u64 {
+        match self.mode {
+            ClaimMode::IfUnclaimed => self.wait_secs,
+            ClaimMode::Always | ClaimMode::IfProfitable => 0,
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+/// How close a task is to its deadline. Drives log severity and the fee bid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Urgency {
+    Normal,
+    Elevated,
+    Critical,
+    Missed,
+}
+
+impl Urgency {
+    pub const NAMES: [&'static str; 4] = ["normal", "elevated", "critical", "missed"];
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES[self as usize]
+    }
+}
+
+/// Escalation thresholds, in seconds left before a task's deadline, and the
+/// fee bid (percent of the provider's EIP-1559 estimate) used at each level.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeadlineConfig {
+    pub elevated_secs: u64,
+    pub critical_secs: u64,
+    pub elevated_fee_percent: u64,
+    pub critical_fee_percent: u64,
+}
+
+impl Default for DeadlineConfig {
+    fn default() -> Self {
+        Self {
+            elevated_secs: 6 * 3600,
+            critical_secs: 3600,
+            elevated_fee_percent: 150,
+            critical_fee_percent: 300,
+        }
+    }
+}
+
+impl DeadlineConfig {
+    pub fn urgency(&self, deadline: Option<u64>, now: u64) -> Urgency {
+        let Some(deadline) = deadline else { return Urgency::Normal };
+        match deadline.checked_sub(now) {
+            None | Some(0) => Urgency::Missed,
+            Some(left) if left <= self.critical_secs => Urgency::Critical,
+            Some(left) if left <= self.elevated_secs => Urgency::Elevated,
+            Some(_) => Urgency::Normal,
+        }
+    }
+
+    /// A missed deadline still bids high: the window may be padded and the
+    /// transaction can still land.
+    pub fn fee_percent(&self, urgency: Urgency) -> u64 {
+        match urgency {
+            Urgency::Normal => 100,
+            Urgency::Elevated => self.elevated_fee_percent,
+            Urgency::Critical | Urgency::Missed => self.critical_fee_percent,
+        }
+    }
+}
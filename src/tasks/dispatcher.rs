@@ -1,18 +1,68 @@
 use alloy::providers::Provider;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
+use tokio::sync::{Notify, Semaphore};
+use tokio::task::JoinHandle;
 use tokio::time::{sleep, Duration};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, instrument, warn, Instrument};
 
+use crate::alerts::{self, Alert, AlertKind};
 use crate::config::{Route, ValidatorConfig};
+use crate::metrics;
 use crate::tasks;
-use crate::tasks::{Task, TaskKind, TaskStore, ClaimStore};
+use crate::tasks::deadline::Urgency;
+use crate::tasks::fees::{self, FeeBid, FeePolicy};
+use crate::tasks::{RouteState, Task, TaskError, TaskKind, TaskStore, ClaimStore};
+use crate::sync::LockExt;
 
 const POLL_INTERVAL: Duration = Duration::from_secs(15);
+const INSUFFICIENT_FUNDS_RETRY_SECS: u64 = 15 * 60;
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DispatcherConfig {
+    /// Epochs whose tasks may execute at the same time.
+    pub max_concurrent_tasks: usize,
+}
+
+impl Default for DispatcherConfig {
+    fn default() -> Self {
+        Self { max_concurrent_tasks: 4 }
+    }
+}
+
+/// Runs ready tasks on a bounded worker pool. Tasks of one epoch build on each
+/// other (claim, verify, withdraw), so they run in order on a single worker;
+/// different epochs run concurrently.
+#[derive(Clone)]
 pub struct TaskDispatcher {
     config: ValidatorConfig,
     route: Route,
     task_store: Arc<Mutex<TaskStore>>,
     claim_store: Arc<Mutex<ClaimStore>>,
+    shutdown: CancellationToken,
+    workers: Arc<Semaphore>,
+    busy_epochs: Arc<Mutex<HashSet<u64>>>,
+    worker_done: Arc<Notify>,
+}
+
+/// Frees the epoch of a finished (or panicked) worker.
+struct EpochGuard {
+    epoch: u64,
+    busy_epochs: Arc<Mutex<HashSet<u64>>>,
+    worker_done: Arc<Notify>,
+}
+
+impl Drop for EpochGuard {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            error!(epoch = self.epoch, "Task panicked, it stays queued");
+        }
+        self.busy_epochs.locked().remove(&self.epoch);
+        self.worker_done.notify_one();
+    }
 }
 
 impl TaskDispatcher {
@@ -22,63 +72,271 @@ impl TaskDispatcher {
         task_store: Arc<Mutex<TaskStore>>,
         claim_store: Arc<Mutex<ClaimStore>>,
     ) -> Self {
+        let workers = Arc::new(Semaphore::new(config.dispatcher.max_concurrent_tasks));
         Self {
             config,
             route,
             task_store,
             claim_store,
+            shutdown: CancellationToken::new(),
+            workers,
+            busy_epochs: Arc::new(Mutex::new(HashSet::new())),
+            worker_done: Arc::new(Notify::new()),
         }
     }
 
+    /// Once `shutdown` is cancelled no new task is started; tasks in flight
+    /// still wait for their receipts.
+    pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// Polls for ready tasks, and again as soon as a worker frees up. Workers
+    /// outlive a restart of this loop, so on shutdown it waits for all of them.
     pub async fn run(&self) {
-        loop {
-            self.process_pending().await;
-            sleep(POLL_INTERVAL).await;
+        while !self.shutdown.is_cancelled() {
+            self.schedule().await;
+            tokio::select! {
+                _ = sleep(POLL_INTERVAL) => {}
+                _ = self.worker_done.notified() => {}
+                _ = self.shutdown.cancelled() => {}
+            }
+        }
+        let in_flight = self.config.dispatcher.max_concurrent_tasks - self.workers.available_permits();
+        if in_flight > 0 {
+            info!(in_flight, "Waiting for in-flight tasks");
         }
+        let _all = self.workers.acquire_many(self.config.dispatcher.max_concurrent_tasks as u32).await;
+        info!("Dispatcher stopped");
     }
 
+    /// Runs every ready task and waits for them to finish.
     pub async fn process_pending(&self) {
-        if !self.task_store.lock().unwrap().is_on_sync() {
-            return;
+        for handle in self.schedule().await {
+            let _ = handle.await;
         }
+    }
+
+    fn report_state(&self, state: &RouteState) {
+        metrics::set_on_sync(&self.route.name, state.on_sync);
+        for kind in TaskKind::NAMES {
+            let count = state.tasks.iter().filter(|t| t.kind.name() == kind).count();
+            metrics::set_pending_tasks(&self.route.name, kind, count);
+        }
+        metrics::set_dead_letters(&self.route.name, state.dead_letters.len());
+        let claims = self.claim_store.locked().all();
+        for honest in ["None", "Claimer", "Challenger"] {
+            let count = claims.iter().filter(|c| c.honest == honest).count();
+            metrics::set_claims(&self.route.name, honest, count);
+        }
+    }
 
-        let state = self.task_store.lock().unwrap().load();
+    /// Hands ready tasks of idle epochs to free workers, most urgent first.
+    #[instrument(name = "dispatcher", skip_all)]
+    async fn schedule(&self) -> Vec<JoinHandle<()>> {
+        // Snapshot busy epochs before loading the queue: a worker removes its
+        // finished task before releasing the epoch, so nothing runs twice.
+        let busy = self.busy_epochs.locked().clone();
+        let state = self.task_store.locked().load();
+        self.report_state(&state);
+        if !state.on_sync {
+            return Vec::new();
+        }
 
         let now = self.route.outbox_provider.get_block_by_number(Default::default()).await
             .expect("Failed to get latest block")
             .expect("Latest block not found")
             .header.timestamp;
 
-        let ready: Vec<Task> = state
+        self.check_deadlines(&state, now);
+
+        // A task about to miss its deadline skips the rest of its failure backoff.
+        let mut ready: Vec<Task> = state
             .tasks
             .iter()
-            .filter(|t| now >= t.execute_after)
+            .filter(|t| !busy.contains(&t.epoch))
+            .filter(|t| now >= t.due_at() || (now >= t.execute_after && self.urgency(t, now) >= Urgency::Critical))
             .cloned()
             .collect();
 
         if ready.is_empty() {
-            return;
+            return Vec::new();
         }
 
-        println!("[{}][Dispatcher] Processing {} ready tasks", self.route.name, ready.len());
+        ready.sort_by_key(|t| (t.deadline.unwrap_or(u64::MAX), t.due_at()));
+        info!(count = ready.len(), "Processing ready tasks");
 
+        let mut batches: Vec<Vec<Task>> = Vec::new();
         for task in ready {
-            println!("[{}][Dispatcher] Executing {} for epoch {}", self.route.name, task.kind.name(), task.epoch);
-            let success = self.execute_task(&task, now).await;
-            if success {
-                self.task_store.lock().unwrap().remove_task(&task);
+            match batches.iter_mut().find(|b| b[0].epoch == task.epoch) {
+                Some(batch) => batch.push(task),
+                None => batches.push(vec![task]),
+            }
+        }
+
+        let mut handles = Vec::new();
+        for batch in batches {
+            let Ok(permit) = self.workers.clone().try_acquire_owned() else {
+                info!("All workers busy, remaining tasks wait for the next poll");
+                break;
+            };
+            let epoch = batch[0].epoch;
+            self.busy_epochs.locked().insert(epoch);
+            let guard = EpochGuard { epoch, busy_epochs: self.busy_epochs.clone(), worker_done: self.worker_done.clone() };
+            let this = self.clone();
+            handles.push(tokio::spawn(async move {
+                let _guard = guard;
+                let _permit = permit;
+                this.run_batch(batch, now).await;
+            }.in_current_span()));
+        }
+        handles
+    }
+
+    async fn run_batch(&self, batch: Vec<Task>, now: u64) {
+        for task in batch {
+            if self.shutdown.is_cancelled() {
+                info!("Shutdown requested, leaving remaining tasks queued");
+                break;
+            }
+            let urgency = self.urgency(&task, now);
+            let span = tracing::info_span!("task", epoch = task.epoch, kind = task.kind.name());
+            match urgency {
+                Urgency::Normal => info!(parent: &span, "Executing task"),
+                Urgency::Elevated => warn!(parent: &span, deadline = task.deadline, secs_left = task.deadline.unwrap_or(now).saturating_sub(now), "Executing task, deadline approaching"),
+                Urgency::Critical | Urgency::Missed => error!(parent: &span, deadline = task.deadline, urgency = urgency.name(), "Executing task at its deadline"),
+            }
+            let policy = self.fee_policy(&task.kind);
+            if let Some(base_fee) = self.base_fee_over_limit(&task.kind, &policy).instrument(span.clone()).await {
+                info!(parent: &span, base_fee, defer_secs = policy.defer_secs, "Base fee above limit, deferring low-priority task");
+                metrics::record_task(&self.route.name, task.kind.name(), "deferred");
+                self.task_store.locked().reschedule_task(&task, now + policy.defer_secs);
+                continue;
             }
+            let bid = policy.bid(task.kind.fee_tier(), self.config.deadlines.fee_percent(urgency));
+            let result = self.execute_task(&task, now, bid).instrument(span.clone()).await;
+            let _enter = span.enter();
+            self.apply_policy(&task, now, result);
         }
     }
 
-    async fn execute_task(&self, task: &Task, current_timestamp: u64) -> bool {
+    fn fee_policy(&self, kind: &TaskKind) -> FeePolicy {
+        if kind.on_inbox() { self.route.inbox_fees } else { self.route.outbox_fees }
+    }
+
+    /// The chain's base fee, if it is above the limit for this kind's tier.
+    /// A failed lookup does not hold the task back.
+    async fn base_fee_over_limit(&self, kind: &TaskKind, policy: &FeePolicy) -> Option<u128> {
+        policy.defer_above_base_fee?;
+        let provider = if kind.on_inbox() { &self.route.inbox_provider } else { &self.route.outbox_provider };
+        match fees::base_fee(provider).await {
+            Ok(Some(base_fee)) if policy.should_defer(kind.fee_tier(), base_fee) => Some(base_fee),
+            Ok(_) => None,
+            Err(e) => {
+                warn!(error = %e, "Failed to read base fee");
+                None
+            }
+        }
+    }
+
+    fn urgency(&self, task: &Task, now: u64) -> Urgency {
+        self.config.deadlines.urgency(task.deadline, now)
+    }
+
+    /// Publishes urgency counts and records each task whose deadline passed
+    /// while it was still queued, once.
+    fn check_deadlines(&self, state: &RouteState, now: u64) {
+        let mut counts = [0usize; Urgency::NAMES.len()];
+        for task in state.tasks.iter().filter(|t| t.deadline.is_some()) {
+            let urgency = self.urgency(task, now);
+            counts[urgency as usize] += 1;
+            let recorded = state.missed_deadlines.iter().any(|m| m.epoch == task.epoch && m.kind == task.kind.name());
+            if urgency == Urgency::Missed && !recorded {
+                error!(epoch = task.epoch, kind = task.kind.name(), deadline = task.deadline, "Deadline missed");
+                metrics::record_missed_deadline(&self.route.name, task.kind.name());
+                self.task_store.locked().record_missed_deadline(task, now);
+            }
+        }
+        for (urgency, count) in Urgency::NAMES.iter().zip(counts) {
+            metrics::set_deadline_tasks(&self.route.name, urgency, count);
+        }
+    }
+
+    /// One policy per `TaskError` variant: completed or moot tasks leave the
+    /// queue, waits are rescheduled, transient failures back off per the
+    /// kind's `RetryPolicy` and are dead-lettered once it is exhausted.
+    fn apply_policy(&self, task: &Task, now: u64, result: Result<(), TaskError>) {
+        let label = match &result {
+            Ok(()) => "success",
+            Err(e) => e.name(),
+        };
+        metrics::record_task(&self.route.name, task.kind.name(), label);
+
+        let task_store = self.task_store.locked();
+        match result {
+            Ok(()) => task_store.remove_task(task),
+            Err(TaskError::RaceLost) => {
+                info!("Lost race to another validator, dropping task");
+                task_store.remove_task(task);
+            }
+            Err(e @ TaskError::Fatal(_)) => {
+                error!(error = %e, "Fatal task error, dead-lettering task");
+                task_store.dead_letter(task, &e.to_string());
+                self.alert_failure(task, &e, true);
+            }
+            Err(TaskError::Retryable { delay, reason }) => {
+                info!(delay, reason = %reason, "Task not ready, rescheduling");
+                task_store.reschedule_task(task, now + delay);
+            }
+            Err(TaskError::InsufficientFunds(msg)) => {
+                warn!(error = %msg, "Insufficient funds, rescheduling");
+                task_store.reschedule_task(task, now + INSUFFICIENT_FUNDS_RETRY_SECS);
+                alerts::fire(Alert::new(AlertKind::LowBalance, &self.route.name, task.kind.name(), format!(
+                    "{} for epoch {} cannot be paid: {}", task.kind.name(), task.epoch, msg,
+                )));
+            }
+            Err(e @ (TaskError::RpcUnavailable(_) | TaskError::Reverted { .. })) => {
+                let policy = self.config.retry.policy(task.kind.name());
+                let attempts = task.attempts + 1;
+                let exhausted = policy.exhausted(attempts);
+                if exhausted {
+                    error!(error = %e, attempts, "Retry budget exhausted, dead-lettering task");
+                    task_store.dead_letter(task, &e.to_string());
+                } else {
+                    let backoff = policy.backoff(attempts);
+                    warn!(error = %e, attempts, retry_in_secs = backoff, "Task failed, backing off");
+                    task_store.record_failure(task, &e.to_string(), now + backoff);
+                }
+                self.alert_failure(task, &e, exhausted);
+            }
+        }
+    }
+
+    /// Every failed challenge pages, since it may let a fraudulent claim
+    /// through; other kinds only once they are dead-lettered.
+    fn alert_failure(&self, task: &Task, e: &TaskError, dead_lettered: bool) {
+        let subject = format!("{} {}", task.epoch, task.kind.name());
+        if matches!(task.kind, TaskKind::Challenge) {
+            alerts::fire(Alert::new(AlertKind::ChallengeFailed, &self.route.name, &subject, format!(
+                "challenge of epoch {} failed (attempt {}): {}", task.epoch, task.attempts + 1, e,
+            )));
+        }
+        if dead_lettered {
+            alerts::fire(Alert::new(AlertKind::DeadLetter, &self.route.name, &subject, format!(
+                "{} for epoch {} dead-lettered: {}", task.kind.name(), task.epoch, e,
+            )));
+        }
+    }
+
+    async fn execute_task(&self, task: &Task, current_timestamp: u64, bid: FeeBid) -> Result<(), TaskError> {
         let epoch = task.epoch;
         match &task.kind {
             TaskKind::SaveSnapshot => {
-                tasks::save_snapshot::execute(&self.route, epoch).await.is_ok()
+                tasks::save_snapshot::execute(&self.route, epoch, bid).await
             }
             TaskKind::Claim { .. } => {
-                tasks::claim::execute(&self.route, epoch, &self.claim_store, current_timestamp).await.is_ok()
+                tasks::claim::execute(&self.route, epoch, &self.claim_store, current_timestamp, bid).await
             }
             TaskKind::ValidateClaim => {
                 tasks::validate_claim::execute(
@@ -87,33 +345,22 @@ impl TaskDispatcher {
                     &self.claim_store,
                     current_timestamp,
                     &self.task_store,
-                ).await.is_ok()
+                ).await
             }
             TaskKind::Challenge => {
-                match tasks::challenge::execute(&self.config, &self.route, epoch, &self.claim_store).await {
-                    Ok(_) => true,
-                    Err(e) if e.to_string() == "Insufficient funds" => {
-                        self.task_store.lock().unwrap().reschedule_task(task, current_timestamp + 15 * 60);
-                        true
-                    }
-                    Err(e) if e.to_string() == "VerificationStarted" => {
-                        self.task_store.lock().unwrap().reschedule_task(task, current_timestamp + 15 * 60);
-                        true
-                    }
-                    Err(_) => false,
-                }
+                tasks::challenge::execute(&self.config, &self.route, epoch, &self.claim_store, bid).await
             }
             TaskKind::SendSnapshot => {
-                tasks::send_snapshot::execute(&self.route, epoch, &self.claim_store).await.is_ok()
+                tasks::send_snapshot::execute(&self.route, epoch, &self.claim_store, bid).await
             }
             TaskKind::StartVerification => {
-                tasks::start_verification::execute(&self.route, epoch, &self.claim_store).await.is_ok()
+                tasks::start_verification::execute(&self.route, epoch, &self.claim_store, bid).await
             }
             TaskKind::VerifySnapshot => {
-                tasks::verify_snapshot::execute(&self.route, epoch, &self.claim_store).await.is_ok()
+                tasks::verify_snapshot::execute(&self.route, epoch, &self.claim_store, bid).await
             }
             TaskKind::ExecuteRelay { position, l2_sender, dest_addr, l2_block, l1_block, l2_timestamp, amount, data } => {
-                match tasks::execute_relay::execute(
+                tasks::execute_relay::execute(
                     &self.route,
                     self.config.arb_outbox,
                     *position,
@@ -124,17 +371,11 @@ impl TaskDispatcher {
                     *l2_timestamp,
                     *amount,
                     data.clone(),
-                ).await {
-                    Ok(_) => true,
-                    Err(e) if e.to_string() == "RootNotConfirmed" => {
-                        self.task_store.lock().unwrap().reschedule_task(task, current_timestamp + 60 * 60);
-                        true
-                    }
-                    Err(_) => false,
-                }
+                    bid,
+                ).await
             }
             TaskKind::WithdrawDeposit => {
-                tasks::withdraw_deposit::execute(&self.route, epoch, &self.claim_store).await.is_ok()
+                tasks::withdraw_deposit::execute(&self.route, epoch, &self.claim_store, bid).await
             }
         }
     }
@@ -0,0 +1,108 @@
+use alloy::contract::Error as ContractError;
+use alloy::providers::PendingTransactionError;
+use alloy::transports::TransportError;
+use std::fmt;
+
+use super::decode_revert_reason;
+
+/// Outcome of a failed `tasks::*::execute`. The dispatcher maps every variant
+/// to one policy instead of inspecting error strings.
+#[derive(Debug)]
+pub enum TaskError {
+    /// A precondition is not met yet; try again after `delay` seconds.
+    Retryable { delay: u64, reason: String },
+    /// Another validator already performed the action on-chain.
+    RaceLost,
+    /// Retrying cannot succeed; the task is dead-lettered.
+    Fatal(String),
+    /// Wallet cannot cover the deposit or gas.
+    InsufficientFunds(String),
+    /// RPC endpoint unreachable or the receipt never arrived.
+    RpcUnavailable(String),
+    /// The call or transaction reverted.
+    Reverted { reason: String },
+}
+
+impl fmt::Display for TaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskError::Retryable { delay, reason } => write!(f, "{} (retry in {}s)", reason, delay),
+            TaskError::RaceLost => write!(f, "already done by another validator"),
+            TaskError::Fatal(msg) => write!(f, "fatal: {}", msg),
+            TaskError::InsufficientFunds(msg) => write!(f, "insufficient funds: {}", msg),
+            TaskError::RpcUnavailable(msg) => write!(f, "RPC unavailable: {}", msg),
+            TaskError::Reverted { reason } => write!(f, "reverted: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for TaskError {}
+
+/// Outbox revert reasons meaning another validator already made the call.
+const RACE_LOST_REVERTS: [&str; 4] = [
+    "Claim already made.",
+    "Claim already challenged.",
+    "Verification already started.",
+    "Claim already verified.",
+];
+
+impl TaskError {
+    /// Outbox reverts such as "Claim already made." mean another validator got there first.
+    pub fn or_race_lost(self) -> Self {
+        match self {
+            TaskError::Reverted { reason } if RACE_LOST_REVERTS.contains(&reason.as_str()) => TaskError::RaceLost,
+            other => other,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TaskError::Retryable { .. } => "retryable",
+            TaskError::RaceLost => "race_lost",
+            TaskError::Fatal(_) => "fatal",
+            TaskError::InsufficientFunds(_) => "insufficient_funds",
+            TaskError::RpcUnavailable(_) => "rpc_unavailable",
+            TaskError::Reverted { .. } => "reverted",
+        }
+    }
+}
+
+/// Classifies a JSON-RPC error response: gas/deposit shortfalls become
+/// `InsufficientFunds`, anything else is a revert with its decoded reason.
+fn from_error_response(msg: String) -> TaskError {
+    if msg.contains("insufficient funds") {
+        return TaskError::InsufficientFunds(msg);
+    }
+    TaskError::Reverted { reason: decode_revert_reason(&msg).unwrap_or(msg) }
+}
+
+impl From<TransportError> for TaskError {
+    fn from(e: TransportError) -> Self {
+        if e.is_error_resp() {
+            from_error_response(e.to_string())
+        } else {
+            TaskError::RpcUnavailable(e.to_string())
+        }
+    }
+}
+
+impl From<ContractError> for TaskError {
+    fn from(e: ContractError) -> Self {
+        match e {
+            ContractError::TransportError(t) => t.into(),
+            ContractError::PendingTransactionError(p) => p.into(),
+            // Undecodable or empty (`0x`) results come from a lagging or
+            // faulty node, not from the task.
+            other => TaskError::RpcUnavailable(other.to_string()),
+        }
+    }
+}
+
+impl From<PendingTransactionError> for TaskError {
+    fn from(e: PendingTransactionError) -> Self {
+        match e {
+            PendingTransactionError::TransportError(t) => t.into(),
+            other => TaskError::RpcUnavailable(other.to_string()),
+        }
+    }
+}
@@ -1,11 +1,14 @@
 use alloy::primitives::{Address, Bytes, FixedBytes, U256};
 use crate::config::Route;
 use crate::contracts::{IArbSys, INodeInterface, IOutbox};
-use crate::tasks::send_tx;
+use crate::tasks::fees::FeeBid;
+use crate::tasks::{send_tx, TaskError};
+use tracing::{info, warn};
 
 const ARB_SYS: Address = Address::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x64]);
 const NODE_INTERFACE: Address = Address::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xC8]);
 
+#[allow(clippy::too_many_arguments)]
 pub async fn execute(
     route: &Route,
     arb_outbox_address: Address,
@@ -17,12 +20,13 @@ pub async fn execute(
     l2_timestamp: u64,
     amount: U256,
     data: Bytes,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bid: FeeBid,
+) -> Result<(), TaskError> {
     let outbox = IOutbox::new(arb_outbox_address, route.outbox_provider.clone());
 
     let is_spent = outbox.isSpent(position).call().await?;
     if is_spent {
-        println!("[{}][task::execute_relay] position {} already spent", route.name, position);
+        info!(position = %position, "Already spent");
         return Ok(());
     }
 
@@ -30,8 +34,8 @@ pub async fn execute(
 
     let root_exists = outbox.roots(root).call().await?;
     if root_exists.is_zero() {
-        println!("[{}][task::execute_relay] root {:#x} not yet confirmed in Outbox, rescheduling", route.name, root);
-        return Err("RootNotConfirmed".into());
+        info!(root = %root, "Root not yet confirmed in Outbox, rescheduling");
+        return Err(TaskError::Retryable { delay: 60 * 60, reason: format!("root {} not confirmed in Outbox", root) });
     }
 
     let result = send_tx(
@@ -45,23 +49,30 @@ pub async fn execute(
             U256::from(l2_timestamp),
             amount,
             data,
-        ).send().await,
+        ),
+        &route.outbox_nonces,
+        bid,
         "executeTransaction",
-        route.name,
-        &[],
+        &route.name,
     ).await;
 
-    if let Err(e) = &result {
-        println!("[{}][task::execute_relay] {}, dropping task", route.name, e);
-        return Ok(());
+    match result {
+        Err(TaskError::Reverted { reason }) => {
+            if outbox.isSpent(position).call().await? {
+                info!(position = %position, "Already relayed by someone else");
+                return Err(TaskError::RaceLost);
+            }
+            warn!(reason = %reason, "Relay reverted, dropping task");
+            Err(TaskError::Fatal(format!("executeTransaction reverted: {}", reason)))
+        }
+        result => result,
     }
-    result
 }
 
 async fn fetch_outbox_proof(
     route: &Route,
     position: U256,
-) -> Result<(Vec<FixedBytes<32>>, FixedBytes<32>), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<(Vec<FixedBytes<32>>, FixedBytes<32>), TaskError> {
     let arb_sys = IArbSys::new(ARB_SYS, route.inbox_provider.clone());
     let state = arb_sys.sendMerkleTreeState().from(Address::ZERO).call().await?;
     let size = state.size;
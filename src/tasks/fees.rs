@@ -0,0 +1,131 @@
+use alloy::eips::eip1559::Eip1559Estimation;
+use alloy::eips::BlockNumberOrTag;
+use alloy::network::Ethereum;
+use alloy::providers::{DynProvider, Provider};
+use serde::{Deserialize, Serialize};
+
+use crate::tasks::TaskError;
+
+/// How much a task kind may pay. A `Challenge` or `SendSnapshot` that comes
+/// late costs a deposit; a late `WithdrawDeposit` or `SaveSnapshot` costs
+/// nothing but time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeTier {
+    Low,
+    Normal,
+    High,
+}
+
+impl FeeTier {
+    pub const NAMES: [&'static str; 3] = ["low", "normal", "high"];
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES[self as usize]
+    }
+}
+
+/// Bid for one tier: `fee_percent` of the provider's EIP-1559 estimate,
+/// clamped to the caps (in wei).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TierPolicy {
+    pub fee_percent: u64,
+    pub max_fee_per_gas: Option<u128>,
+    pub max_priority_fee_per_gas: Option<u128>,
+}
+
+impl Default for TierPolicy {
+    fn default() -> Self {
+        Self { fee_percent: 100, max_fee_per_gas: None, max_priority_fee_per_gas: None }
+    }
+}
+
+/// Fee policy of one chain. While the base fee is above
+/// `defer_above_base_fee` (wei), low-tier tasks wait `defer_secs` and try again.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeePolicy {
+    pub low: TierPolicy,
+    pub normal: TierPolicy,
+    pub high: TierPolicy,
+    pub defer_above_base_fee: Option<u128>,
+    pub defer_secs: u64,
+}
+
+impl Default for FeePolicy {
+    fn default() -> Self {
+        Self {
+            low: TierPolicy::default(),
+            normal: TierPolicy::default(),
+            high: TierPolicy { fee_percent: 150, ..TierPolicy::default() },
+            defer_above_base_fee: None,
+            defer_secs: 10 * 60,
+        }
+    }
+}
+
+impl FeePolicy {
+    pub fn tier(&self, tier: FeeTier) -> &TierPolicy {
+        match tier {
+            FeeTier::Low => &self.low,
+            FeeTier::Normal => &self.normal,
+            FeeTier::High => &self.high,
+        }
+    }
+
+    /// The bid for a task of `tier` whose deadline asks for `deadline_percent`.
+    /// Urgency raises the multiplier but never lifts the tier's caps.
+    pub fn bid(&self, tier: FeeTier, deadline_percent: u64) -> FeeBid {
+        let policy = self.tier(tier);
+        FeeBid {
+            fee_percent: policy.fee_percent.max(deadline_percent),
+            max_fee_per_gas: policy.max_fee_per_gas,
+            max_priority_fee_per_gas: policy.max_priority_fee_per_gas,
+        }
+    }
+
+    pub fn should_defer(&self, tier: FeeTier, base_fee: u128) -> bool {
+        tier == FeeTier::Low && self.defer_above_base_fee.is_some_and(|limit| base_fee > limit)
+    }
+}
+
+/// What `send_tx` may pay for one transaction, including its replacements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeeBid {
+    pub fee_percent: u64,
+    pub max_fee_per_gas: Option<u128>,
+    pub max_priority_fee_per_gas: Option<u128>,
+}
+
+impl Default for FeeBid {
+    fn default() -> Self {
+        Self { fee_percent: 100, max_fee_per_gas: None, max_priority_fee_per_gas: None }
+    }
+}
+
+impl FeeBid {
+    /// Scales `estimate` and clamps it to the caps. The priority fee never
+    /// exceeds the max fee, which nodes reject.
+    pub fn price(&self, estimate: Eip1559Estimation) -> (u128, u128) {
+        let scale = |fee: u128| fee.saturating_mul(self.fee_percent as u128) / 100;
+        let max_fee = self.cap_max_fee(scale(estimate.max_fee_per_gas));
+        let priority_fee = self.cap_priority_fee(scale(estimate.max_priority_fee_per_gas)).min(max_fee);
+        (max_fee, priority_fee)
+    }
+
+    pub fn cap_max_fee(&self, fee: u128) -> u128 {
+        self.max_fee_per_gas.map_or(fee, |cap| fee.min(cap))
+    }
+
+    pub fn cap_priority_fee(&self, fee: u128) -> u128 {
+        self.max_priority_fee_per_gas.map_or(fee, |cap| fee.min(cap))
+    }
+}
+
+/// Base fee of the latest block; `None` on chains without EIP-1559.
+pub async fn base_fee(provider: &DynProvider<Ethereum>) -> Result<Option<u128>, TaskError> {
+    let block = provider.get_block_by_number(BlockNumberOrTag::Latest).await?
+        .ok_or_else(|| TaskError::RpcUnavailable("latest block not found".to_string()))?;
+    Ok(block.header.base_fee_per_gas.map(u128::from))
+}
@@ -1,4 +1,11 @@
 pub mod dispatcher;
+pub mod deadline;
+pub mod error;
+pub mod claim_policy;
+pub mod fees;
+pub mod nonce;
+pub mod recorder;
+pub mod retry;
 pub mod save_snapshot;
 pub mod claim;
 pub mod validate_claim;
@@ -8,17 +15,25 @@ pub mod start_verification;
 pub mod verify_snapshot;
 pub mod execute_relay;
 pub mod withdraw_deposit;
+pub mod storage;
 
 use alloy::primitives::{Address, Bytes, FixedBytes, U256};
-use alloy::contract::Error as ContractError;
+use alloy::contract::{CallBuilder, CallDecoder};
 use alloy::network::Ethereum;
-use alloy::providers::{DynProvider, PendingTransactionBuilder, Provider};
+use alloy::providers::{DynProvider, Provider};
 use alloy::rpc::types::Filter;
 use serde::{Deserialize, Serialize};
-use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{error, info, warn, Instrument};
 
 use crate::contracts::{Claim, Party};
+use crate::metrics;
+use crate::tasks::fees::{FeeBid, FeeTier};
+use crate::tasks::nonce::NonceManager;
+use crate::tasks::storage::{JsonBackend, StateBackend, StoreOp};
+
+pub use error::TaskError;
 
 fn decode_revert_reason(err_msg: &str) -> Option<String> {
     let data_prefix = "data: \"0x";
@@ -36,25 +51,21 @@ fn decode_revert_reason(err_msg: &str) -> Option<String> {
 
     let bytes = alloy::hex::decode(hex_data).ok()?;
 
-    if bytes.len() >= 4 && bytes[0..4] == [0x08, 0xc3, 0x79, 0xa0] {
-        if bytes.len() >= 68 {
-            let offset = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
-            if bytes.len() >= 36 + offset {
-                let len_start = 4 + offset;
-                let len = u32::from_be_bytes([bytes[len_start + 28], bytes[len_start + 29], bytes[len_start + 30], bytes[len_start + 31]]) as usize;
-                let str_start = len_start + 32;
-                if bytes.len() >= str_start + len {
-                    return String::from_utf8(bytes[str_start..str_start + len].to_vec()).ok();
-                }
+    if bytes.len() >= 68 && bytes[0..4] == [0x08, 0xc3, 0x79, 0xa0] {
+        let offset = U256::from_be_slice(&bytes[4..36]).saturating_to::<usize>().min(bytes.len());
+        let len_start = 4 + offset;
+        if bytes.len() >= len_start + 32 {
+            let len = U256::from_be_slice(&bytes[len_start..len_start + 32]).saturating_to::<usize>().min(bytes.len());
+            let str_start = len_start + 32;
+            if bytes.len() >= str_start + len {
+                return String::from_utf8(bytes[str_start..str_start + len].to_vec()).ok();
             }
         }
     }
 
-    if bytes.len() >= 4 && bytes[0..4] == [0x4e, 0x48, 0x7b, 0x71] {
-        if bytes.len() >= 36 {
-            let code = U256::from_be_slice(&bytes[4..36]);
-            return Some(format!("Panic(0x{:02x})", code));
-        }
+    if bytes.len() >= 36 && bytes[0..4] == [0x4e, 0x48, 0x7b, 0x71] {
+        let code = U256::from_be_slice(&bytes[4..36]);
+        return Some(format!("Panic(0x{:02x})", code));
     }
 
     if bytes.len() >= 4 {
@@ -109,35 +120,30 @@ pub async fn was_event_emitted(
     }
 }
 
-pub async fn send_tx(
-    result: Result<PendingTransactionBuilder<Ethereum>, ContractError>,
+/// Waits for the receipt of a sent transaction. Reverts come back as
+/// `TaskError::Reverted`; callers confirm lost races from on-chain state.
+pub async fn send_tx<P: Provider, D: CallDecoder>(
+    call: CallBuilder<P, D>,
+    nonces: &NonceManager,
+    bid: FeeBid,
     action: &str,
     route_name: &str,
-    race_ok: &[&str],
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    match result {
-        Ok(pending) => {
-            let receipt = pending.get_receipt().await?;
-            if !receipt.status() {
-                return Err(format!("[{}] {} reverted", route_name, action).into());
-            }
-            println!("[{}] {} succeeded", route_name, action);
-            Ok(())
+) -> Result<(), TaskError> {
+    let request = call.into_transaction_request();
+    if let Some(recorder) = nonces.recorder() {
+        recorder.record(nonces.chain(), route_name, action, &request);
+        return Ok(());
+    }
+    let span = tracing::info_span!("tx", action);
+    async {
+        let receipt = nonces.submit(request, bid, action).await?;
+        metrics::record_tx(route_name, action, receipt.gas_used, receipt.effective_gas_price, receipt.status());
+        if !receipt.status() {
+            return Err(TaskError::Reverted { reason: format!("{} reverted on-chain", action) });
         }
-        Err(e) => {
-            let err_msg = e.to_string();
-            for pattern in race_ok {
-                if err_msg.contains(pattern) {
-                    println!("[{}] {} already done", route_name, action);
-                    return Ok(());
-                }
-            }
-            match decode_revert_reason(&err_msg) {
-                Some(reason) => Err(format!("[{}] {} reverted: {}", route_name, action, reason).into()),
-                None => Err(e.into()),
-            }
-        }
-    }
+        info!(gas_used = receipt.gas_used, "{} succeeded", action);
+        Ok(())
+    }.instrument(span).await
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -145,6 +151,45 @@ pub struct Task {
     pub epoch: u64,
     pub execute_after: u64,
     pub kind: TaskKind,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_attempt: Option<u64>,
+    /// Latest time the task can still achieve its purpose, see `RouteSettings`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<u64>,
+}
+
+impl Task {
+    pub fn new(epoch: u64, execute_after: u64, kind: TaskKind) -> Self {
+        Self { epoch, execute_after, kind, attempts: 0, last_error: None, next_attempt: None, deadline: None }
+    }
+
+    pub fn with_deadline(mut self, deadline: u64) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Earliest time the dispatcher may run the task: its schedule, pushed
+    /// back by failure backoff.
+    pub fn due_at(&self) -> u64 {
+        self.next_attempt.map_or(self.execute_after, |t| t.max(self.execute_after))
+    }
+
+    pub(crate) fn record_failure(&mut self, error: &str, next_attempt: Option<u64>) {
+        self.attempts += 1;
+        self.last_error = Some(error.to_string());
+        self.next_attempt = next_attempt;
+    }
+
+    pub(crate) fn reset(&mut self, execute_after: u64) {
+        self.execute_after = execute_after;
+        self.attempts = 0;
+        self.last_error = None;
+        self.next_attempt = None;
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -174,6 +219,18 @@ pub enum TaskKind {
 }
 
 impl TaskKind {
+    pub const NAMES: [&'static str; 9] = [
+        "SaveSnapshot",
+        "Claim",
+        "ValidateClaim",
+        "Challenge",
+        "SendSnapshot",
+        "StartVerification",
+        "VerifySnapshot",
+        "ExecuteRelay",
+        "WithdrawDeposit",
+    ];
+
     pub fn name(&self) -> &'static str {
         match self {
             TaskKind::SaveSnapshot => "SaveSnapshot",
@@ -187,6 +244,24 @@ impl TaskKind {
             TaskKind::WithdrawDeposit => "WithdrawDeposit",
         }
     }
+
+    pub fn fee_tier(&self) -> FeeTier {
+        match self {
+            TaskKind::Challenge | TaskKind::SendSnapshot => FeeTier::High,
+            TaskKind::SaveSnapshot | TaskKind::WithdrawDeposit => FeeTier::Low,
+            _ => FeeTier::Normal,
+        }
+    }
+
+    /// Whether the task transacts on the inbox chain rather than the outbox chain.
+    pub fn on_inbox(&self) -> bool {
+        matches!(self, TaskKind::SaveSnapshot | TaskKind::SendSnapshot)
+    }
+
+    /// Whether the task acts on the epoch's indexed outbox claim.
+    pub fn follows_claim(&self) -> bool {
+        !matches!(self, TaskKind::SaveSnapshot | TaskKind::Claim { .. } | TaskKind::ExecuteRelay { .. })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -197,6 +272,63 @@ pub struct RouteState {
     pub indexing_since: Option<u64>,
     #[serde(default)]
     pub on_sync: bool,
+    #[serde(default)]
+    pub dead_letters: Vec<Task>,
+    #[serde(default)]
+    pub missed_deadlines: Vec<MissedDeadline>,
+    /// Oldest first, at most `storage::MAX_CHECKPOINTS` per chain.
+    #[serde(default)]
+    pub checkpoints: Vec<Checkpoint>,
+    /// Undo entries for updates since each chain's latest checkpoint.
+    #[serde(default)]
+    pub pending_undo: Vec<(IndexedChain, Undo)>,
+}
+
+/// A chain the indexer scans.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexedChain {
+    Inbox,
+    Outbox,
+}
+
+impl IndexedChain {
+    pub fn name(self) -> &'static str {
+        match self {
+            IndexedChain::Inbox => "inbox",
+            IndexedChain::Outbox => "outbox",
+        }
+    }
+}
+
+/// Reverses one update the indexer derived from a log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Undo {
+    RemoveTask { epoch: u64, kind: String },
+    RestoreTask { task: Task },
+    RemoveClaim { epoch: u64 },
+    RestoreClaim { claim: ClaimData },
+}
+
+/// A block the indexer scanned up to, with the undo entries for what it
+/// derived from the logs since the previous checkpoint on the same chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub chain: IndexedChain,
+    pub block: u64,
+    pub hash: FixedBytes<32>,
+    #[serde(default)]
+    pub undo: Vec<Undo>,
+}
+
+/// A task that was still queued when its deadline passed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissedDeadline {
+    pub epoch: u64,
+    pub kind: String,
+    pub deadline: u64,
+    pub detected_at: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -212,55 +344,53 @@ pub struct ClaimData {
 }
 
 pub struct ClaimStore {
-    path: PathBuf,
+    backend: Arc<dyn StateBackend>,
 }
 
 impl ClaimStore {
     pub fn new(path: impl Into<PathBuf>) -> Self {
-        Self { path: path.into() }
+        Self::with_backend(Arc::new(JsonBackend::new(None, Some(path.into()))))
     }
 
-    fn load_all(&self) -> Vec<ClaimData> {
-        match fs::read_to_string(&self.path) {
-            Ok(contents) => serde_json::from_str(&contents).expect("Failed to parse claims file - data corrupted"),
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
-            Err(e) => panic!("Failed to read claims file: {}", e),
-        }
+    pub fn with_backend(backend: Arc<dyn StateBackend>) -> Self {
+        Self { backend }
     }
 
-    fn save_all(&self, claims: &[ClaimData]) {
-        if let Some(parent) = self.path.parent() {
-            fs::create_dir_all(parent).expect("Failed to create claims directory");
-        }
-        let contents = serde_json::to_string_pretty(claims).expect("Failed to serialize claims");
-        fs::write(&self.path, contents).expect("Failed to write claims file");
+    pub fn backend(&self) -> &Arc<dyn StateBackend> {
+        &self.backend
+    }
+
+    fn load_all(&self) -> Vec<ClaimData> {
+        self.backend.load_claims()
     }
 
     pub fn store(&self, claim: ClaimData) {
-        let mut claims = self.load_all();
-        let existing = claims.iter().filter(|c| c.epoch == claim.epoch).count();
-        if existing > 0 {
-            panic!("Duplicate claim for epoch {} - this should never happen", claim.epoch);
-        }
-        claims.push(claim);
-        self.save_all(&claims);
+        self.backend.apply(&[StoreOp::StoreClaim(claim)]);
     }
 
-    pub fn update<F>(&self, epoch: u64, f: F)
+    /// Applies `f` to the stored claim and returns the op that persists it,
+    /// for batching with other updates via `storage::commit`.
+    pub fn prepare_update<F>(&self, epoch: u64, f: F) -> StoreOp
     where
         F: FnOnce(&mut ClaimData),
     {
-        let mut claims = self.load_all();
-        let claim = claims.iter_mut().find(|c| c.epoch == epoch);
-        match claim {
-            Some(c) => {
-                f(c);
-                self.save_all(&claims);
+        match self.backend.get_claim(epoch) {
+            Some(mut c) => {
+                f(&mut c);
+                StoreOp::ReplaceClaim(c)
             }
             None => panic!("Cannot update claim for epoch {} - not found", epoch),
         }
     }
 
+    pub fn update<F>(&self, epoch: u64, f: F)
+    where
+        F: FnOnce(&mut ClaimData),
+    {
+        let op = self.prepare_update(epoch, f);
+        self.backend.apply(&[op]);
+    }
+
     pub fn get(&self, epoch: u64) -> ClaimData {
         let claims = self.load_all();
         let matches: Vec<_> = claims.into_iter().filter(|c| c.epoch == epoch).collect();
@@ -288,10 +418,12 @@ impl ClaimStore {
         }
     }
 
+    pub fn all(&self) -> Vec<ClaimData> {
+        self.load_all()
+    }
+
     pub fn remove(&self, epoch: u64) {
-        let mut claims = self.load_all();
-        claims.retain(|c| c.epoch != epoch);
-        self.save_all(&claims);
+        self.backend.apply(&[StoreOp::RemoveClaim(epoch)]);
     }
 
     pub fn has_state_root_in_recent_claims(&self, state_root: FixedBytes<32>, since_timestamp: u32) -> bool {
@@ -300,96 +432,146 @@ impl ClaimStore {
     }
 
     pub fn exists(&self, epoch: u64) -> bool {
-        self.load_all().iter().any(|c| c.epoch == epoch)
+        self.backend.get_claim(epoch).is_some()
     }
 }
 
 pub struct TaskStore {
-    path: PathBuf,
+    label: String,
+    backend: Arc<dyn StateBackend>,
 }
 
 impl TaskStore {
     pub fn new(path: impl Into<PathBuf>) -> Self {
-        Self { path: path.into() }
+        let path = path.into();
+        let label = path.file_stem()
+            .map(|s| s.to_string_lossy().to_uppercase().replace("-", "_"))
+            .unwrap_or_default();
+        Self { label, backend: Arc::new(JsonBackend::new(Some(path), None)) }
     }
 
-    fn label(&self) -> String {
-        self.path.file_stem()
-            .map(|s| s.to_string_lossy().to_uppercase().replace("-", "_"))
-            .unwrap_or_default()
+    pub fn with_backend(label: &str, backend: Arc<dyn StateBackend>) -> Self {
+        Self { label: label.to_string(), backend }
     }
 
-    pub fn load(&self) -> RouteState {
-        match fs::read_to_string(&self.path) {
-            Ok(contents) => serde_json::from_str(&contents).expect("Failed to parse schedule file - data corrupted"),
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => RouteState::default(),
-            Err(e) => panic!("Failed to read schedule file: {}", e),
-        }
+    pub fn backend(&self) -> &Arc<dyn StateBackend> {
+        &self.backend
     }
 
-    pub fn save(&self, state: &RouteState) {
-        if let Some(parent) = self.path.parent() {
-            fs::create_dir_all(parent).expect("Failed to create schedules directory");
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub(crate) fn log_ops(&self, ops: &[StoreOp]) {
+        for op in ops {
+            match op {
+                StoreOp::AddTask(task) => {
+                    info!(store = self.label(), epoch = task.epoch, kind = task.kind.name(), execute_after = task.execute_after, "Scheduling task");
+                }
+                StoreOp::RescheduleTask { epoch, kind, execute_after } => {
+                    info!(store = self.label(), epoch, kind, execute_after, "Rescheduling task");
+                }
+                StoreOp::RecordFailure { epoch, kind, error, next_attempt } => {
+                    warn!(store = self.label(), epoch, kind, next_attempt, error = %error, "Task failed, backing off");
+                }
+                StoreOp::DeadLetterTask { epoch, kind, error } => {
+                    error!(store = self.label(), epoch, kind, error = %error, "Task moved to dead letters");
+                }
+                StoreOp::RequeueDeadLetter { epoch, kind, execute_after } => {
+                    info!(store = self.label(), epoch, kind, execute_after, "Requeueing dead-lettered task");
+                }
+                StoreOp::RecordMissedDeadline(missed) => {
+                    error!(store = self.label(), epoch = missed.epoch, kind = %missed.kind, deadline = missed.deadline, "Recording missed deadline");
+                }
+                _ => {}
+            }
         }
-        let contents = serde_json::to_string_pretty(state).expect("Failed to serialize state");
-        fs::write(&self.path, contents).expect("Failed to write state file");
+    }
+
+    fn apply(&self, ops: &[StoreOp]) {
+        self.log_ops(ops);
+        self.backend.apply(ops);
+    }
+
+    pub fn load(&self) -> RouteState {
+        self.backend.load_state()
+    }
+
+    pub fn find_task(&self, epoch: u64, kind: &str) -> Option<Task> {
+        self.load().tasks.into_iter().find(|t| t.epoch == epoch && t.kind.name() == kind)
     }
 
     pub fn add_task(&self, task: Task) {
-        println!("[{}][TaskStore] Scheduling {} for epoch {} at {}", self.label(), task.kind.name(), task.epoch, task.execute_after);
-        let mut state = self.load();
-        state.tasks.push(task);
-        self.save(&state);
+        self.apply(&[StoreOp::AddTask(task)]);
     }
 
     pub fn remove_task(&self, task: &Task) {
-        let mut state = self.load();
-        state.tasks.retain(|t| !(t.epoch == task.epoch && t.kind.name() == task.kind.name()));
-        self.save(&state);
+        self.apply(&[StoreOp::RemoveTask { epoch: task.epoch, kind: task.kind.name() }]);
     }
 
     pub fn reschedule_task(&self, task: &Task, execute_after: u64) {
-        println!("[{}][TaskStore] Rescheduling {} for epoch {} to {}", self.label(), task.kind.name(), task.epoch, execute_after);
-        let mut state = self.load();
-        for t in &mut state.tasks {
-            if t.epoch == task.epoch && t.kind.name() == task.kind.name() {
-                t.execute_after = execute_after;
-                break;
-            }
-        }
-        self.save(&state);
+        self.apply(&[StoreOp::RescheduleTask { epoch: task.epoch, kind: task.kind.name(), execute_after }]);
+    }
+
+    pub fn record_failure(&self, task: &Task, error: &str, next_attempt: u64) {
+        self.apply(&[StoreOp::RecordFailure { epoch: task.epoch, kind: task.kind.name(), error: error.to_string(), next_attempt }]);
+    }
+
+    pub fn dead_letter(&self, task: &Task, error: &str) {
+        self.apply(&[StoreOp::DeadLetterTask { epoch: task.epoch, kind: task.kind.name(), error: error.to_string() }]);
+    }
+
+    pub fn find_dead_letter(&self, epoch: u64, kind: &str) -> Option<Task> {
+        self.load().dead_letters.into_iter().find(|t| t.epoch == epoch && t.kind.name() == kind)
+    }
+
+    pub fn requeue_dead_letter(&self, task: &Task, execute_after: u64) {
+        self.apply(&[StoreOp::RequeueDeadLetter { epoch: task.epoch, kind: task.kind.name(), execute_after }]);
+    }
+
+    /// Records the miss once per task; later calls for the same task are no-ops.
+    pub fn record_missed_deadline(&self, task: &Task, detected_at: u64) {
+        let Some(deadline) = task.deadline else { return };
+        let missed = MissedDeadline { epoch: task.epoch, kind: task.kind.name().to_string(), deadline, detected_at };
+        self.apply(&[StoreOp::RecordMissedDeadline(missed)]);
+    }
+
+    pub fn remove_dead_letter(&self, task: &Task) {
+        self.apply(&[StoreOp::RemoveDeadLetter { epoch: task.epoch, kind: task.kind.name() }]);
     }
 
     pub fn update_inbox_block(&self, block: u64) {
-        let mut state = self.load();
-        state.inbox_last_block = Some(block);
-        self.save(&state);
+        self.apply(&[StoreOp::SetInboxBlock(block)]);
     }
 
     pub fn update_outbox_block(&self, block: u64) {
-        let mut state = self.load();
-        state.outbox_last_block = Some(block);
-        self.save(&state);
+        self.apply(&[StoreOp::SetOutboxBlock(block)]);
     }
 
     pub fn set_indexing_since(&self, ts: u64) {
-        let mut state = self.load();
-        state.indexing_since = Some(ts);
-        self.save(&state);
+        self.apply(&[StoreOp::SetIndexingSince(ts)]);
+    }
+
+    /// Moves `chain`'s cursor to `block` and checkpoints it with its hash.
+    pub fn advance_cursor(&self, chain: IndexedChain, block: u64, hash: FixedBytes<32>) {
+        let cursor = match chain {
+            IndexedChain::Inbox => StoreOp::SetInboxBlock(block),
+            IndexedChain::Outbox => StoreOp::SetOutboxBlock(block),
+        };
+        self.apply(&[cursor, StoreOp::Checkpoint { chain, block, hash }]);
     }
 
     pub fn initialize_sync(&self, indexing_since: u64, inbox_block: u64, outbox_block: u64) {
-        let mut state = self.load();
-        state.indexing_since = Some(indexing_since);
-        state.inbox_last_block = Some(inbox_block);
-        state.outbox_last_block = Some(outbox_block);
-        self.save(&state);
+        self.apply(&[
+            StoreOp::ClearCheckpoints,
+            StoreOp::SetIndexingSince(indexing_since),
+            StoreOp::SetInboxBlock(inbox_block),
+            StoreOp::SetOutboxBlock(outbox_block),
+        ]);
     }
 
     pub fn set_on_sync(&self, value: bool) {
-        let mut state = self.load();
-        state.on_sync = value;
-        self.save(&state);
+        self.apply(&[StoreOp::SetOnSync(value)]);
     }
 
     pub fn is_on_sync(&self) -> bool {
@@ -0,0 +1,351 @@
+use alloy::network::Ethereum;
+use alloy::primitives::{keccak256, Address, TxHash, TxKind, B256};
+use alloy::providers::{DynProvider, Provider};
+use alloy::rpc::types::{TransactionReceipt, TransactionRequest};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+use crate::metrics;
+use crate::tasks::fees::FeeBid;
+use crate::tasks::recorder::TxRecorder;
+use crate::tasks::storage::json::{read_json, write_json, DEFAULT_BACKUPS};
+use crate::tasks::TaskError;
+use crate::sync::LockExt;
+
+/// When and how far a stuck transaction is re-priced.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TxConfig {
+    /// Blocks a transaction may stay pending before it is replaced.
+    pub replace_after_blocks: u64,
+    /// Fee increase per replacement; nodes reject replacements below 10%.
+    pub bump_percent: u64,
+    /// Highest bid, as percent of the first one.
+    pub fee_cap_percent: u64,
+}
+
+impl Default for TxConfig {
+    fn default() -> Self {
+        Self {
+            replace_after_blocks: 5,
+            bump_percent: 20,
+            fee_cap_percent: 400,
+        }
+    }
+}
+
+/// A submitted transaction that has not been mined yet. Every replacement
+/// reuses `nonce`, so any of `hashes` may be the one that lands.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTx {
+    pub key: B256,
+    pub action: String,
+    pub nonce: u64,
+    pub hashes: Vec<TxHash>,
+    pub request: TransactionRequest,
+    pub initial_max_fee_per_gas: u128,
+    pub initial_max_priority_fee_per_gas: u128,
+    pub sent_block: u64,
+    #[serde(default)]
+    pub bid: FeeBid,
+}
+
+impl PendingTx {
+    /// Highest max fee and priority fee a replacement may bid.
+    fn caps(&self, cap_percent: u64) -> (u128, u128) {
+        let cap = |initial: u128| initial.saturating_mul(cap_percent as u128) / 100;
+        (
+            self.bid.cap_max_fee(cap(self.initial_max_fee_per_gas)),
+            self.bid.cap_priority_fee(cap(self.initial_max_priority_fee_per_gas)),
+        )
+    }
+
+    fn at_cap(&self, cap_percent: u64) -> bool {
+        self.request.max_fee_per_gas.unwrap_or_default() >= self.caps(cap_percent).0
+    }
+}
+
+/// Assigns nonces for the shared wallet on one chain and sees each
+/// transaction through to a receipt, replacing it with a higher fee when it
+/// sits in the mempool for `replace_after_blocks`. Routes share one manager
+/// per chain, so concurrent tasks never sign the same nonce.
+///
+/// Pending transactions are journaled (see `persist_to`); a task retried
+/// after a restart resumes waiting for its earlier transaction instead of
+/// sending a second one.
+pub struct NonceManager {
+    chain: String,
+    provider: DynProvider<Ethereum>,
+    wallet: Address,
+    config: TxConfig,
+    poll_interval: Duration,
+    next_nonce: tokio::sync::Mutex<Option<u64>>,
+    pending: Mutex<Vec<PendingTx>>,
+    journal: OnceLock<PathBuf>,
+    recorder: Option<Arc<TxRecorder>>,
+}
+
+/// Identifies a transaction by what it does, so re-running a task finds the
+/// transaction it sent before.
+fn request_key(request: &TransactionRequest) -> B256 {
+    let mut buf = Vec::new();
+    if let Some(TxKind::Call(to)) = request.to {
+        buf.extend_from_slice(to.as_slice());
+    }
+    if let Some(input) = request.input.input() {
+        buf.extend_from_slice(input);
+    }
+    buf.extend_from_slice(&request.value.unwrap_or_default().to_be_bytes::<32>());
+    keccak256(buf)
+}
+
+/// Where the pending transactions of the wallet on `chain_id` are journaled.
+pub fn journal_path(data_dir: impl AsRef<Path>, chain_id: u64) -> PathBuf {
+    data_dir.as_ref().join("txs").join(format!("{}.json", chain_id))
+}
+
+pub fn read_journal(path: &Path) -> Vec<PendingTx> {
+    read_json(path, DEFAULT_BACKUPS, "pending transactions").unwrap_or_default()
+}
+
+fn bump(fee: u128, percent: u64, cap: u128) -> u128 {
+    (fee * (100 + percent as u128) / 100).min(cap)
+}
+
+impl NonceManager {
+    pub fn new(chain: &str, provider: DynProvider<Ethereum>, wallet: Address, config: TxConfig, avg_block_millis: u32) -> Self {
+        Self {
+            chain: chain.to_string(),
+            provider,
+            wallet,
+            config,
+            poll_interval: Duration::from_millis(avg_block_millis.max(1000) as u64),
+            next_nonce: tokio::sync::Mutex::new(None),
+            pending: Mutex::new(Vec::new()),
+            journal: OnceLock::new(),
+            recorder: None,
+        }
+    }
+
+    /// Dry-run mode: `send_tx` hands transactions to `recorder` instead of
+    /// this manager.
+    pub fn with_recorder(mut self, recorder: Arc<TxRecorder>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    pub fn recorder(&self) -> Option<&TxRecorder> {
+        self.recorder.as_deref()
+    }
+
+    pub fn chain(&self) -> &str {
+        &self.chain
+    }
+
+    /// Journals pending transactions to `path` and loads the ones left by a
+    /// previous run. Only the first call per manager takes effect.
+    pub fn persist_to(&self, path: PathBuf) {
+        let loaded = read_journal(&path);
+        if self.journal.set(path).is_err() {
+            return;
+        }
+        if !loaded.is_empty() {
+            info!(chain = %self.chain, count = loaded.len(), "Loaded pending transactions");
+        }
+        *self.pending.locked() = loaded;
+    }
+
+    pub fn wallet(&self) -> Address {
+        self.wallet
+    }
+
+    pub fn pending(&self) -> Vec<PendingTx> {
+        self.pending.locked().clone()
+    }
+
+    fn save(&self, pending: &[PendingTx]) {
+        if let Some(path) = self.journal.get() {
+            write_json(path, pending, DEFAULT_BACKUPS).expect("Failed to write pending transactions");
+        }
+    }
+
+    fn upsert(&self, tx: &PendingTx) {
+        let mut pending = self.pending.locked();
+        pending.retain(|p| p.key != tx.key);
+        pending.push(tx.clone());
+        self.save(&pending);
+    }
+
+    fn forget(&self, key: B256) {
+        let mut pending = self.pending.locked();
+        pending.retain(|p| p.key != key);
+        self.save(&pending);
+    }
+
+    /// Sends `request` priced by `bid` (or resumes the identical one still
+    /// pending, re-priced if `bid` allows more) and waits for its receipt.
+    pub async fn submit(&self, request: TransactionRequest, bid: FeeBid, action: &str) -> Result<TransactionReceipt, TaskError> {
+        let key = request_key(&request);
+        let existing = self.pending.locked().iter().find(|p| p.key == key).cloned();
+        let tx = match existing {
+            Some(mut tx) => {
+                info!(chain = %self.chain, nonce = tx.nonce, tx_hash = %tx.hashes[tx.hashes.len() - 1], "Resuming pending transaction");
+                if bid != tx.bid {
+                    self.rebid(&mut tx, bid).await?;
+                }
+                tx
+            }
+            None => self.broadcast(request, bid, key, action).await?,
+        };
+        self.wait_mined(tx).await
+    }
+
+    async fn broadcast(&self, mut request: TransactionRequest, bid: FeeBid, key: B256, action: &str) -> Result<PendingTx, TaskError> {
+        if request.max_fee_per_gas.is_none() {
+            let (max_fee, priority_fee) = bid.price(self.provider.estimate_eip1559_fees().await?);
+            if bid != FeeBid::default() {
+                info!(chain = %self.chain, fee_percent = bid.fee_percent, max_fee_per_gas = max_fee, "Bidding by fee policy");
+            }
+            request.max_fee_per_gas = Some(max_fee);
+            request.max_priority_fee_per_gas = Some(priority_fee);
+        }
+
+        let sent_block = self.provider.get_block_number().await?;
+
+        let mut next_nonce = self.next_nonce.lock().await;
+        let nonce = match *next_nonce {
+            Some(nonce) => nonce,
+            None => self.provider.get_transaction_count(self.wallet).pending().await?,
+        };
+        request.from = Some(self.wallet);
+        request.nonce = Some(nonce);
+
+        let hash = match self.provider.send_transaction(request.clone()).await {
+            Ok(pending) => *pending.tx_hash(),
+            Err(e) => {
+                // The node may or may not have taken the nonce; ask it next time.
+                *next_nonce = None;
+                return Err(e.into());
+            }
+        };
+        *next_nonce = Some(nonce + 1);
+        drop(next_nonce);
+
+        info!(chain = %self.chain, nonce, tx_hash = %hash, "Transaction sent");
+        let tx = PendingTx {
+            key,
+            action: action.to_string(),
+            nonce,
+            hashes: vec![hash],
+            initial_max_fee_per_gas: request.max_fee_per_gas.unwrap_or_default(),
+            initial_max_priority_fee_per_gas: request.max_priority_fee_per_gas.unwrap_or_default(),
+            request,
+            sent_block,
+            bid,
+        };
+        self.upsert(&tx);
+        Ok(tx)
+    }
+
+    async fn receipt(&self, tx: &PendingTx) -> Result<Option<TransactionReceipt>, TaskError> {
+        for hash in tx.hashes.iter().rev() {
+            if let Some(receipt) = self.provider.get_transaction_receipt(*hash).await? {
+                return Ok(Some(receipt));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn wait_mined(&self, mut tx: PendingTx) -> Result<TransactionReceipt, TaskError> {
+        loop {
+            if let Some(receipt) = self.receipt(&tx).await? {
+                self.forget(tx.key);
+                return Ok(receipt);
+            }
+
+            let mined_nonce = self.provider.get_transaction_count(self.wallet).await?;
+            if mined_nonce > tx.nonce {
+                // Mined between the two calls, or the nonce went to a different transaction.
+                if let Some(receipt) = self.receipt(&tx).await? {
+                    self.forget(tx.key);
+                    return Ok(receipt);
+                }
+                self.forget(tx.key);
+                return Err(TaskError::RpcUnavailable(format!("nonce {} was used by another transaction", tx.nonce)));
+            }
+
+            let block = self.provider.get_block_number().await?;
+            if block >= tx.sent_block + self.config.replace_after_blocks {
+                if tx.at_cap(self.config.fee_cap_percent) {
+                    warn!(chain = %self.chain, nonce = tx.nonce, "Transaction still pending at the fee cap");
+                    return Err(TaskError::RpcUnavailable(format!(
+                        "{} pending for {} blocks at the fee cap", tx.action, block - tx.sent_block,
+                    )));
+                }
+                self.replace(&mut tx, block).await;
+            }
+            sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Adopts `bid` for a resumed transaction if it allows higher fees than
+    /// the one it was sent with, e.g. escalated near a deadline, and replaces
+    /// it at the same nonce right away if the new price beats the current one.
+    async fn rebid(&self, tx: &mut PendingTx, bid: FeeBid) -> Result<(), TaskError> {
+        let (max_fee, priority_fee) = bid.price(self.provider.estimate_eip1559_fees().await?);
+        let rebid = PendingTx {
+            bid,
+            initial_max_fee_per_gas: max_fee,
+            initial_max_priority_fee_per_gas: priority_fee,
+            ..tx.clone()
+        };
+        if rebid.caps(self.config.fee_cap_percent).0 <= tx.caps(self.config.fee_cap_percent).0 {
+            return Ok(());
+        }
+        info!(chain = %self.chain, nonce = tx.nonce, fee_percent = bid.fee_percent, max_fee_per_gas = max_fee, "Re-pricing resumed transaction");
+        *tx = rebid;
+        if max_fee > tx.request.max_fee_per_gas.unwrap_or_default() {
+            // Nodes only take a replacement that outbids the pending one by
+            // enough, so pay at least a regular bump.
+            let (max_fee_cap, priority_fee_cap) = tx.caps(self.config.fee_cap_percent);
+            let max_fee = max_fee.max(bump(tx.request.max_fee_per_gas.unwrap_or_default(), self.config.bump_percent, max_fee_cap));
+            let priority_fee = priority_fee.max(bump(tx.request.max_priority_fee_per_gas.unwrap_or_default(), self.config.bump_percent, priority_fee_cap));
+            let block = self.provider.get_block_number().await?;
+            self.replace_with(tx, block, max_fee, priority_fee).await;
+        } else {
+            self.upsert(tx);
+        }
+        Ok(())
+    }
+
+    async fn replace(&self, tx: &mut PendingTx, block: u64) {
+        let (max_fee_cap, priority_fee_cap) = tx.caps(self.config.fee_cap_percent);
+        let max_fee = bump(tx.request.max_fee_per_gas.unwrap_or_default(), self.config.bump_percent, max_fee_cap);
+        let priority_fee = bump(tx.request.max_priority_fee_per_gas.unwrap_or_default(), self.config.bump_percent, priority_fee_cap);
+        self.replace_with(tx, block, max_fee, priority_fee).await;
+    }
+
+    async fn replace_with(&self, tx: &mut PendingTx, block: u64, max_fee: u128, priority_fee: u128) {
+        let mut request = tx.request.clone();
+        request.max_fee_per_gas = Some(max_fee);
+        request.max_priority_fee_per_gas = Some(priority_fee.min(max_fee));
+
+        match self.provider.send_transaction(request.clone()).await {
+            Ok(pending) => {
+                let hash = *pending.tx_hash();
+                warn!(chain = %self.chain, nonce = tx.nonce, tx_hash = %hash, max_fee_per_gas = request.max_fee_per_gas, "Replaced stuck transaction");
+                metrics::record_tx_replacement(&self.chain, &tx.action);
+                tx.hashes.push(hash);
+            }
+            // Either underpriced (the next round bids higher) or the original
+            // was mined meanwhile (the next receipt check tells).
+            Err(e) => warn!(chain = %self.chain, nonce = tx.nonce, error = %e, "Replacement rejected"),
+        }
+        tx.request = request;
+        tx.sent_block = block;
+        self.upsert(tx);
+    }
+}
@@ -0,0 +1,62 @@
+use alloy::primitives::{Address, Bytes, TxKind, U256};
+use alloy::rpc::types::TransactionRequest;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::info;
+
+use crate::metrics;
+use crate::sync::LockExt;
+
+const MAX_RECORDED: usize = 1000;
+
+/// A transaction the validator decided to send while in dry-run mode.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordedTx {
+    pub chain: String,
+    pub route: String,
+    pub action: String,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub input: Bytes,
+    pub recorded_at: u64,
+}
+
+/// Stands in for broadcasting in dry-run mode: `send_tx` logs and keeps the
+/// transaction it would have sent (the most recent `MAX_RECORDED`) and
+/// reports success, so the pipeline carries on as if it had been mined.
+#[derive(Default)]
+pub struct TxRecorder {
+    txs: Mutex<VecDeque<RecordedTx>>,
+}
+
+impl TxRecorder {
+    pub fn record(&self, chain: &str, route: &str, action: &str, request: &TransactionRequest) {
+        let to = match request.to {
+            Some(TxKind::Call(to)) => Some(to),
+            _ => None,
+        };
+        let tx = RecordedTx {
+            chain: chain.to_string(),
+            route: route.to_string(),
+            action: action.to_string(),
+            to,
+            value: request.value.unwrap_or_default(),
+            input: request.input.input().cloned().unwrap_or_default(),
+            recorded_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        };
+        info!(chain, to = ?tx.to, value = %tx.value, input = %tx.input, "Dry run: would send {}", action);
+        metrics::record_dry_run_tx(route, action);
+
+        let mut txs = self.txs.locked();
+        if txs.len() == MAX_RECORDED {
+            txs.pop_front();
+        }
+        txs.push_back(tx);
+    }
+
+    pub fn recorded(&self) -> Vec<RecordedTx> {
+        self.txs.locked().iter().cloned().collect()
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Backoff for a task that failed with a transient error (`RpcUnavailable` or
+/// `Reverted`). After `max_attempts` failures the task is dead-lettered;
+/// `None` retries forever.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    pub initial_backoff_secs: u64,
+    pub max_backoff_secs: u64,
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff_secs: 15,
+            max_backoff_secs: 30 * 60,
+            max_attempts: Some(50),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before the attempt following the `attempts`-th failure.
+    pub fn backoff(&self, attempts: u32) -> u64 {
+        let exp = attempts.saturating_sub(1).min(32);
+        self.initial_backoff_secs
+            .saturating_mul(2u64.saturating_pow(exp))
+            .min(self.max_backoff_secs)
+    }
+
+    pub fn exhausted(&self, attempts: u32) -> bool {
+        self.max_attempts.is_some_and(|max| attempts >= max)
+    }
+}
+
+/// Per-`TaskKind` overrides on top of a default policy. A challenge is the
+/// last line of defence against a fraudulent claim, so it never dead-letters
+/// unless configured to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    pub default: RetryPolicy,
+    pub kinds: HashMap<String, RetryPolicy>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        let mut kinds = HashMap::new();
+        kinds.insert("Challenge".to_string(), RetryPolicy { max_attempts: None, ..RetryPolicy::default() });
+        Self { default: RetryPolicy::default(), kinds }
+    }
+}
+
+impl RetryConfig {
+    pub fn policy(&self, kind: &str) -> RetryPolicy {
+        self.kinds.get(kind).copied().unwrap_or(self.default)
+    }
+}
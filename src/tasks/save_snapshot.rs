@@ -2,12 +2,14 @@ use alloy::primitives::U256;
 use alloy::providers::Provider;
 use crate::config::Route;
 use crate::contracts::IVeaInbox;
-use crate::tasks::send_tx;
+use crate::tasks::fees::FeeBid;
+use crate::tasks::{send_tx, TaskError};
 
 pub async fn execute(
     route: &Route,
     epoch: u64,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bid: FeeBid,
+) -> Result<(), TaskError> {
     let inbox = IVeaInbox::new(route.inbox_address, route.inbox_provider.clone());
 
     let epoch_period = inbox.epochPeriod().call().await?.to::<u64>();
@@ -40,15 +42,15 @@ pub async fn execute(
     }
 
     let snapshot_logs = snapshot_logs?;
-    if let Some(last_snapshot) = snapshot_logs.last() {
-        if last_snapshot.data().data.len() >= 96 {
-            let saved_count = U256::from_be_slice(&last_snapshot.data().data[64..96]).to::<u64>();
-            let current_count = inbox.count().call().await?;
-            if saved_count == current_count {
-                return Ok(());
-            }
+    if let Some(last_snapshot) = snapshot_logs.last()
+        && last_snapshot.data().data.len() >= 96
+    {
+        let saved_count = U256::from_be_slice(&last_snapshot.data().data[64..96]).to::<u64>();
+        let current_count = inbox.count().call().await?;
+        if saved_count == current_count {
+            return Ok(());
         }
     }
 
-    send_tx(inbox.saveSnapshot().send().await, "saveSnapshot", route.name, &[]).await
+    send_tx(inbox.saveSnapshot(), &route.inbox_nonces, bid, "saveSnapshot", &route.name).await
 }
@@ -2,35 +2,40 @@ use alloy::primitives::{Address, U256};
 use std::sync::{Arc, Mutex};
 use crate::config::Route;
 use crate::contracts::IVeaOutbox;
-use crate::tasks::{send_tx, was_event_emitted, ClaimStore};
+use crate::tasks::fees::FeeBid;
+use crate::tasks::{send_tx, was_event_emitted, ClaimStore, TaskError};
+use tracing::info;
+use crate::sync::LockExt;
 
 pub async fn execute(
     route: &Route,
     epoch: u64,
     claim_store: &Arc<Mutex<ClaimStore>>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let claim_data = claim_store.lock().unwrap().get(epoch);
+    bid: FeeBid,
+) -> Result<(), TaskError> {
+    let claim_data = claim_store.locked().get(epoch);
     if claim_data.challenger != Address::ZERO {
-        println!("[{}][task::start_verification] Epoch {} already challenged, dropping task", route.name, epoch);
+        info!(epoch, "Already challenged, dropping task");
         return Ok(());
     }
 
-    let claim = claim_store.lock().unwrap().get_claim(epoch);
+    let claim = claim_store.locked().get_claim(epoch);
     let outbox = IVeaOutbox::new(route.outbox_address, route.outbox_provider.clone());
     let result = send_tx(
-        outbox.startVerification(U256::from(epoch), claim).send().await,
+        outbox.startVerification(U256::from(epoch), claim),
+        &route.outbox_nonces,
+        bid,
         "startVerification",
-        route.name,
-        &["already"],
-    ).await;
+        &route.name,
+    ).await.map_err(TaskError::or_race_lost);
 
     if let Err(e) = result {
         if was_event_emitted(&route.outbox_provider, route.outbox_address, "VerificationStarted(uint256)", epoch).await {
-            println!("[{}][task::start_verification] Epoch {} already started by another validator", route.name, epoch);
-            return Ok(());
+            info!(epoch, "Already started by another validator");
+            return Err(TaskError::RaceLost);
         }
         if was_event_emitted(&route.outbox_provider, route.outbox_address, "Challenged(uint256,address)", epoch).await {
-            println!("[{}][task::start_verification] Epoch {} was challenged, dropping task", route.name, epoch);
+            info!(epoch, "Challenged, dropping task");
             return Ok(());
         }
         return Err(e);
@@ -0,0 +1,157 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::tasks::{ClaimData, RouteState};
+use crate::tasks::storage::{StateBackend, StoreOp};
+
+pub const DEFAULT_BACKUPS: usize = 3;
+
+/// Pretty-printed JSON files, one for the schedule and one for claims.
+/// A batch touching both files writes claims first, then the schedule.
+///
+/// Files are replaced atomically (temp file, fsync, rename) and the previous
+/// `backups` versions are kept as `<file>.bak.1` (newest) .. `<file>.bak.N`.
+/// A file that fails to parse is restored from the newest valid backup.
+pub struct JsonBackend {
+    schedule_path: Option<PathBuf>,
+    claims_path: Option<PathBuf>,
+    backups: usize,
+}
+
+impl JsonBackend {
+    pub fn new(schedule_path: Option<PathBuf>, claims_path: Option<PathBuf>) -> Self {
+        Self { schedule_path, claims_path, backups: DEFAULT_BACKUPS }
+    }
+
+    pub fn with_backups(mut self, backups: usize) -> Self {
+        self.backups = backups;
+        self
+    }
+
+    pub fn schedule_path(&self) -> Option<&Path> {
+        self.schedule_path.as_deref()
+    }
+
+    fn schedule(&self) -> &Path {
+        self.schedule_path.as_deref().expect("JSON backend has no schedule file")
+    }
+
+    fn claims(&self) -> &Path {
+        self.claims_path.as_deref().expect("JSON backend has no claims file")
+    }
+
+    fn save_state(&self, state: &RouteState) {
+        write_json(self.schedule(), state, self.backups).expect("Failed to write state file");
+    }
+
+    fn save_claims(&self, claims: &[ClaimData]) {
+        write_json(self.claims(), &claims, self.backups).expect("Failed to write claims file");
+    }
+}
+
+pub fn backup_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(format!(".bak.{}", n));
+    PathBuf::from(name)
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".tmp");
+    PathBuf::from(name)
+}
+
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+pub(crate) fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T, backups: usize) -> std::io::Result<()> {
+    let contents = serde_json::to_string_pretty(value).expect("Failed to serialize state");
+    write_atomic(path, contents.as_bytes(), backups)
+}
+
+/// Writes `contents` to a temp file, fsyncs it, rotates backups and renames it
+/// over `path`, so a crash at any point leaves either the old or the new file.
+fn write_atomic(path: &Path, contents: &[u8], backups: usize) -> std::io::Result<()> {
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    fs::create_dir_all(parent)?;
+
+    let tmp = tmp_path(path);
+    let mut file = File::create(&tmp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    if backups > 0 && path.exists() {
+        for n in (1..backups).rev() {
+            let from = backup_path(path, n);
+            if from.exists() {
+                fs::rename(&from, backup_path(path, n + 1))?;
+            }
+        }
+        fs::copy(path, backup_path(path, 1))?;
+    }
+
+    fs::rename(&tmp, path)?;
+    sync_dir(parent)
+}
+
+/// Reads `path`, falling back to the newest backup that parses when the file
+/// is corrupt. A recovered backup is written back as the main file.
+/// Returns `None` when the file does not exist.
+pub(crate) fn read_json<T: DeserializeOwned>(path: &Path, backups: usize, what: &str) -> Option<T> {
+    let parse_err = match fs::read_to_string(path) {
+        Ok(contents) => match serde_json::from_str(&contents) {
+            Ok(value) => return Some(value),
+            Err(e) => e,
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(e) => panic!("Failed to read {} file: {}", what, e),
+    };
+
+    for n in 1..=backups {
+        let backup = backup_path(path, n);
+        let Ok(contents) = fs::read_to_string(&backup) else {
+            continue;
+        };
+        if let Ok(value) = serde_json::from_str::<T>(&contents) {
+            warn!(file = %path.display(), backup = %backup.display(), error = %parse_err, "{} file corrupted, recovered from backup", what);
+            write_atomic(path, contents.as_bytes(), 0).expect("Failed to restore file from backup");
+            return Some(value);
+        }
+    }
+
+    panic!("Failed to parse {} file {} ({}) and no valid backup found - data corrupted", what, path.display(), parse_err);
+}
+
+impl StateBackend for JsonBackend {
+    fn load_state(&self) -> RouteState {
+        read_json(self.schedule(), self.backups, "schedule").unwrap_or_default()
+    }
+
+    fn load_claims(&self) -> Vec<ClaimData> {
+        read_json(self.claims(), self.backups, "claims").unwrap_or_default()
+    }
+
+    fn apply(&self, ops: &[StoreOp]) {
+        if ops.iter().any(|op| op.touches_claims()) {
+            let mut claims = self.load_claims();
+            for op in ops {
+                op.apply_to_claims(&mut claims);
+            }
+            self.save_claims(&claims);
+        }
+        if ops.iter().any(|op| !op.touches_claims()) {
+            let mut state = self.load_state();
+            for op in ops {
+                op.apply_to_state(&mut state);
+            }
+            self.save_state(&state);
+        }
+    }
+}
@@ -0,0 +1,205 @@
+pub mod json;
+pub mod sqlite;
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use alloy::primitives::FixedBytes;
+
+use crate::tasks::{Checkpoint, ClaimData, ClaimStore, IndexedChain, MissedDeadline, RouteState, Task, TaskStore, Undo};
+use crate::sync::LockExt;
+
+pub use json::JsonBackend;
+pub use sqlite::SqliteBackend;
+
+/// Checkpoints kept per chain, i.e. how many scans back a reorg can be undone.
+pub const MAX_CHECKPOINTS: usize = 128;
+
+/// A single state mutation. Backends apply a batch of ops atomically, so
+/// e.g. storing a claim and scheduling its `ValidateClaim` either both land
+/// or neither does.
+#[derive(Debug, Clone)]
+pub enum StoreOp {
+    AddTask(Task),
+    RemoveTask { epoch: u64, kind: &'static str },
+    RescheduleTask { epoch: u64, kind: &'static str, execute_after: u64 },
+    RecordFailure { epoch: u64, kind: &'static str, error: String, next_attempt: u64 },
+    DeadLetterTask { epoch: u64, kind: &'static str, error: String },
+    RequeueDeadLetter { epoch: u64, kind: &'static str, execute_after: u64 },
+    RemoveDeadLetter { epoch: u64, kind: &'static str },
+    RecordMissedDeadline(MissedDeadline),
+    SetInboxBlock(u64),
+    SetOutboxBlock(u64),
+    SetIndexingSince(u64),
+    SetOnSync(bool),
+    /// Queues undo entries for the chain's next checkpoint.
+    RecordUndo(IndexedChain, Vec<Undo>),
+    /// Checkpoints `block`, taking the chain's queued undo entries.
+    Checkpoint { chain: IndexedChain, block: u64, hash: FixedBytes<32> },
+    /// Drops the chain's checkpoints after `block` and its queued undo entries.
+    RollBack { chain: IndexedChain, block: u64 },
+    ClearCheckpoints,
+    StoreClaim(ClaimData),
+    ReplaceClaim(ClaimData),
+    RemoveClaim(u64),
+}
+
+impl StoreOp {
+    pub fn touches_claims(&self) -> bool {
+        matches!(self, StoreOp::StoreClaim(_) | StoreOp::ReplaceClaim(_) | StoreOp::RemoveClaim(_))
+    }
+
+    pub(crate) fn apply_to_state(&self, state: &mut RouteState) {
+        match self {
+            StoreOp::AddTask(task) => state.tasks.push(task.clone()),
+            StoreOp::RemoveTask { epoch, kind } => {
+                state.tasks.retain(|t| !(t.epoch == *epoch && t.kind.name() == *kind));
+            }
+            StoreOp::RescheduleTask { epoch, kind, execute_after } => {
+                if let Some(t) = state.tasks.iter_mut().find(|t| t.epoch == *epoch && t.kind.name() == *kind) {
+                    t.execute_after = *execute_after;
+                    t.next_attempt = None;
+                }
+            }
+            StoreOp::RecordFailure { epoch, kind, error, next_attempt } => {
+                if let Some(t) = state.tasks.iter_mut().find(|t| t.epoch == *epoch && t.kind.name() == *kind) {
+                    t.record_failure(error, Some(*next_attempt));
+                }
+            }
+            StoreOp::DeadLetterTask { epoch, kind, error } => {
+                if let Some(pos) = state.tasks.iter().position(|t| t.epoch == *epoch && t.kind.name() == *kind) {
+                    let mut task = state.tasks.remove(pos);
+                    task.record_failure(error, None);
+                    state.dead_letters.push(task);
+                }
+            }
+            StoreOp::RequeueDeadLetter { epoch, kind, execute_after } => {
+                if let Some(pos) = state.dead_letters.iter().position(|t| t.epoch == *epoch && t.kind.name() == *kind) {
+                    let mut task = state.dead_letters.remove(pos);
+                    task.reset(*execute_after);
+                    state.tasks.push(task);
+                }
+            }
+            StoreOp::RemoveDeadLetter { epoch, kind } => {
+                state.dead_letters.retain(|t| !(t.epoch == *epoch && t.kind.name() == *kind));
+            }
+            StoreOp::RecordMissedDeadline(missed) => {
+                if !state.missed_deadlines.iter().any(|m| m.epoch == missed.epoch && m.kind == missed.kind) {
+                    state.missed_deadlines.push(missed.clone());
+                }
+            }
+            StoreOp::SetInboxBlock(block) => state.inbox_last_block = Some(*block),
+            StoreOp::SetOutboxBlock(block) => state.outbox_last_block = Some(*block),
+            StoreOp::SetIndexingSince(ts) => state.indexing_since = Some(*ts),
+            StoreOp::SetOnSync(value) => state.on_sync = *value,
+            StoreOp::RecordUndo(chain, undo) => {
+                state.pending_undo.extend(undo.iter().map(|u| (*chain, u.clone())));
+            }
+            StoreOp::Checkpoint { chain, block, hash } => {
+                let (taken, kept): (Vec<_>, Vec<_>) = state.pending_undo.drain(..).partition(|(c, _)| c == chain);
+                state.pending_undo = kept;
+                state.checkpoints.push(Checkpoint {
+                    chain: *chain,
+                    block: *block,
+                    hash: *hash,
+                    undo: taken.into_iter().map(|(_, u)| u).collect(),
+                });
+                let count = state.checkpoints.iter().filter(|c| c.chain == *chain).count();
+                let mut excess = count.saturating_sub(MAX_CHECKPOINTS);
+                state.checkpoints.retain(|c| {
+                    if c.chain == *chain && excess > 0 {
+                        excess -= 1;
+                        return false;
+                    }
+                    true
+                });
+            }
+            StoreOp::RollBack { chain, block } => {
+                state.checkpoints.retain(|c| !(c.chain == *chain && c.block > *block));
+                state.pending_undo.retain(|(c, _)| c != chain);
+            }
+            StoreOp::ClearCheckpoints => {
+                state.checkpoints.clear();
+                state.pending_undo.clear();
+            }
+            StoreOp::StoreClaim(_) | StoreOp::ReplaceClaim(_) | StoreOp::RemoveClaim(_) => {}
+        }
+    }
+
+    pub(crate) fn apply_to_claims(&self, claims: &mut Vec<ClaimData>) {
+        match self {
+            StoreOp::StoreClaim(claim) => {
+                if claims.iter().any(|c| c.epoch == claim.epoch) {
+                    panic!("Duplicate claim for epoch {} - this should never happen", claim.epoch);
+                }
+                claims.push(claim.clone());
+            }
+            StoreOp::ReplaceClaim(claim) => {
+                match claims.iter_mut().find(|c| c.epoch == claim.epoch) {
+                    Some(c) => *c = claim.clone(),
+                    None => panic!("Cannot update claim for epoch {} - not found", claim.epoch),
+                }
+            }
+            StoreOp::RemoveClaim(epoch) => claims.retain(|c| c.epoch != *epoch),
+            _ => {}
+        }
+    }
+}
+
+/// Persistence for one route's schedule, cursors and claims.
+pub trait StateBackend: Send + Sync {
+    fn load_state(&self) -> RouteState;
+    fn load_claims(&self) -> Vec<ClaimData>;
+    fn apply(&self, ops: &[StoreOp]);
+
+    fn get_claim(&self, epoch: u64) -> Option<ClaimData> {
+        self.load_claims().into_iter().find(|c| c.epoch == epoch)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageKind {
+    #[default]
+    Json,
+    Sqlite,
+}
+
+/// Opens the task and claim stores for a route under `data_dir`, both backed
+/// by the same backend so multi-step updates can be committed together.
+pub fn open_route_stores(kind: StorageKind, data_dir: impl AsRef<Path>, route_name: &str) -> (TaskStore, ClaimStore) {
+    let data_dir = data_dir.as_ref();
+    let file_name = route_name.to_lowercase().replace('_', "-");
+    let backend: Arc<dyn StateBackend> = match kind {
+        StorageKind::Json => Arc::new(JsonBackend::new(
+            Some(data_dir.join("schedules").join(format!("{}.json", file_name))),
+            Some(data_dir.join("claims").join(format!("{}.json", file_name))),
+        )),
+        StorageKind::Sqlite => Arc::new(SqliteBackend::open(data_dir.join(format!("{}.sqlite", file_name)))),
+    };
+    (
+        TaskStore::with_backend(route_name, backend.clone()),
+        ClaimStore::with_backend(backend),
+    )
+}
+
+/// Applies `ops` spanning both stores. When the stores share a backend the
+/// batch is committed in one transaction; stores opened separately fall back
+/// to applying claim ops first, then schedule ops.
+pub fn commit(claim_store: &Arc<Mutex<ClaimStore>>, task_store: &Arc<Mutex<TaskStore>>, ops: Vec<StoreOp>) {
+    let claims = claim_store.locked();
+    let tasks = task_store.locked();
+    tasks.log_ops(&ops);
+    if Arc::ptr_eq(claims.backend(), tasks.backend()) {
+        tasks.backend().apply(&ops);
+        return;
+    }
+    let (claim_ops, task_ops): (Vec<_>, Vec<_>) = ops.into_iter().partition(|op| op.touches_claims());
+    if !claim_ops.is_empty() {
+        claims.backend().apply(&claim_ops);
+    }
+    if !task_ops.is_empty() {
+        tasks.backend().apply(&task_ops);
+    }
+}
@@ -0,0 +1,311 @@
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::tasks::{Checkpoint, ClaimData, IndexedChain, MissedDeadline, RouteState, Task, Undo};
+use crate::tasks::storage::{StateBackend, StoreOp, MAX_CHECKPOINTS};
+use crate::sync::LockExt;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS route_state (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        inbox_last_block INTEGER,
+        outbox_last_block INTEGER,
+        indexing_since INTEGER,
+        on_sync INTEGER NOT NULL DEFAULT 0
+    );
+    INSERT OR IGNORE INTO route_state (id) VALUES (0);
+    CREATE TABLE IF NOT EXISTS tasks (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        epoch INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        task TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS tasks_epoch_kind ON tasks (epoch, kind);
+    CREATE TABLE IF NOT EXISTS dead_letters (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        epoch INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        task TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS missed_deadlines (
+        epoch INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        deadline INTEGER NOT NULL,
+        detected_at INTEGER NOT NULL,
+        PRIMARY KEY (epoch, kind)
+    );
+    CREATE TABLE IF NOT EXISTS claims (
+        epoch INTEGER PRIMARY KEY,
+        claim TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS checkpoints (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        chain TEXT NOT NULL,
+        block INTEGER NOT NULL,
+        checkpoint TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS pending_undo (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        chain TEXT NOT NULL,
+        undo TEXT NOT NULL
+    );
+";
+
+/// Embedded SQLite database holding one route's schedule, cursors and claims.
+/// Every batch of ops runs in a single transaction.
+pub struct SqliteBackend {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteBackend {
+    pub fn open(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("Failed to create data directory");
+        }
+        let conn = Connection::open(path).expect("Failed to open SQLite database");
+        conn.pragma_update(None, "journal_mode", "WAL").expect("Failed to enable WAL");
+        conn.pragma_update(None, "synchronous", "FULL").expect("Failed to set synchronous mode");
+        conn.execute_batch(SCHEMA).expect("Failed to initialize SQLite schema");
+        Self { conn: Mutex::new(conn) }
+    }
+
+    fn find_row(tx: &Transaction, table: &str, epoch: u64, kind: &str) -> rusqlite::Result<Option<(i64, Task)>> {
+        let row: Option<(i64, String)> = tx.query_row(
+            &format!("SELECT id, task FROM {} WHERE epoch = ?1 AND kind = ?2 ORDER BY id LIMIT 1", table),
+            params![epoch as i64, kind],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        ).optional()?;
+        Ok(row.map(|(id, json)| (id, from_json(&json))))
+    }
+
+    fn update_task(tx: &Transaction, epoch: u64, kind: &str, f: impl FnOnce(&mut Task)) -> rusqlite::Result<()> {
+        if let Some((id, mut task)) = Self::find_row(tx, "tasks", epoch, kind)? {
+            f(&mut task);
+            tx.execute("UPDATE tasks SET task = ?1 WHERE id = ?2", params![to_json(&task), id])?;
+        }
+        Ok(())
+    }
+
+    fn apply_op(tx: &Transaction, op: &StoreOp) -> rusqlite::Result<()> {
+        match op {
+            StoreOp::AddTask(task) => {
+                tx.execute(
+                    "INSERT INTO tasks (epoch, kind, task) VALUES (?1, ?2, ?3)",
+                    params![task.epoch as i64, task.kind.name(), to_json(task)],
+                )?;
+            }
+            StoreOp::RemoveTask { epoch, kind } => {
+                tx.execute("DELETE FROM tasks WHERE epoch = ?1 AND kind = ?2", params![*epoch as i64, kind])?;
+            }
+            StoreOp::RescheduleTask { epoch, kind, execute_after } => {
+                Self::update_task(tx, *epoch, kind, |task| {
+                    task.execute_after = *execute_after;
+                    task.next_attempt = None;
+                })?;
+            }
+            StoreOp::RecordFailure { epoch, kind, error, next_attempt } => {
+                Self::update_task(tx, *epoch, kind, |task| task.record_failure(error, Some(*next_attempt)))?;
+            }
+            StoreOp::DeadLetterTask { epoch, kind, error } => {
+                if let Some((id, mut task)) = Self::find_row(tx, "tasks", *epoch, kind)? {
+                    task.record_failure(error, None);
+                    tx.execute("DELETE FROM tasks WHERE id = ?1", params![id])?;
+                    tx.execute(
+                        "INSERT INTO dead_letters (epoch, kind, task) VALUES (?1, ?2, ?3)",
+                        params![task.epoch as i64, task.kind.name(), to_json(&task)],
+                    )?;
+                }
+            }
+            StoreOp::RequeueDeadLetter { epoch, kind, execute_after } => {
+                if let Some((id, mut task)) = Self::find_row(tx, "dead_letters", *epoch, kind)? {
+                    task.reset(*execute_after);
+                    tx.execute("DELETE FROM dead_letters WHERE id = ?1", params![id])?;
+                    tx.execute(
+                        "INSERT INTO tasks (epoch, kind, task) VALUES (?1, ?2, ?3)",
+                        params![task.epoch as i64, task.kind.name(), to_json(&task)],
+                    )?;
+                }
+            }
+            StoreOp::RemoveDeadLetter { epoch, kind } => {
+                tx.execute("DELETE FROM dead_letters WHERE epoch = ?1 AND kind = ?2", params![*epoch as i64, kind])?;
+            }
+            StoreOp::RecordMissedDeadline(missed) => {
+                tx.execute(
+                    "INSERT OR IGNORE INTO missed_deadlines (epoch, kind, deadline, detected_at) VALUES (?1, ?2, ?3, ?4)",
+                    params![missed.epoch as i64, missed.kind, missed.deadline as i64, missed.detected_at as i64],
+                )?;
+            }
+            StoreOp::SetInboxBlock(block) => {
+                tx.execute("UPDATE route_state SET inbox_last_block = ?1", params![*block as i64])?;
+            }
+            StoreOp::SetOutboxBlock(block) => {
+                tx.execute("UPDATE route_state SET outbox_last_block = ?1", params![*block as i64])?;
+            }
+            StoreOp::SetIndexingSince(ts) => {
+                tx.execute("UPDATE route_state SET indexing_since = ?1", params![*ts as i64])?;
+            }
+            StoreOp::SetOnSync(value) => {
+                tx.execute("UPDATE route_state SET on_sync = ?1", params![*value])?;
+            }
+            StoreOp::RecordUndo(chain, undo) => {
+                for u in undo {
+                    tx.execute("INSERT INTO pending_undo (chain, undo) VALUES (?1, ?2)", params![chain.name(), to_json(u)])?;
+                }
+            }
+            StoreOp::Checkpoint { chain, block, hash } => {
+                let mut stmt = tx.prepare("SELECT undo FROM pending_undo WHERE chain = ?1 ORDER BY id")?;
+                let undo: Vec<Undo> = stmt.query_map(params![chain.name()], |r| r.get::<_, String>(0))?
+                    .map(|row| row.map(|json| from_json(&json)))
+                    .collect::<rusqlite::Result<_>>()?;
+                drop(stmt);
+                tx.execute("DELETE FROM pending_undo WHERE chain = ?1", params![chain.name()])?;
+                let checkpoint = Checkpoint { chain: *chain, block: *block, hash: *hash, undo };
+                tx.execute(
+                    "INSERT INTO checkpoints (chain, block, checkpoint) VALUES (?1, ?2, ?3)",
+                    params![chain.name(), *block as i64, to_json(&checkpoint)],
+                )?;
+                tx.execute(
+                    "DELETE FROM checkpoints WHERE chain = ?1 AND id NOT IN
+                        (SELECT id FROM checkpoints WHERE chain = ?1 ORDER BY id DESC LIMIT ?2)",
+                    params![chain.name(), MAX_CHECKPOINTS as i64],
+                )?;
+            }
+            StoreOp::RollBack { chain, block } => {
+                tx.execute("DELETE FROM checkpoints WHERE chain = ?1 AND block > ?2", params![chain.name(), *block as i64])?;
+                tx.execute("DELETE FROM pending_undo WHERE chain = ?1", params![chain.name()])?;
+            }
+            StoreOp::ClearCheckpoints => {
+                tx.execute("DELETE FROM checkpoints", [])?;
+                tx.execute("DELETE FROM pending_undo", [])?;
+            }
+            StoreOp::StoreClaim(claim) => {
+                let inserted = tx.execute(
+                    "INSERT OR IGNORE INTO claims (epoch, claim) VALUES (?1, ?2)",
+                    params![claim.epoch as i64, to_json(claim)],
+                )?;
+                if inserted == 0 {
+                    panic!("Duplicate claim for epoch {} - this should never happen", claim.epoch);
+                }
+            }
+            StoreOp::ReplaceClaim(claim) => {
+                let updated = tx.execute(
+                    "UPDATE claims SET claim = ?1 WHERE epoch = ?2",
+                    params![to_json(claim), claim.epoch as i64],
+                )?;
+                if updated == 0 {
+                    panic!("Cannot update claim for epoch {} - not found", claim.epoch);
+                }
+            }
+            StoreOp::RemoveClaim(epoch) => {
+                tx.execute("DELETE FROM claims WHERE epoch = ?1", params![*epoch as i64])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn indexed_chain(name: &str) -> IndexedChain {
+    match name {
+        "inbox" => IndexedChain::Inbox,
+        "outbox" => IndexedChain::Outbox,
+        other => panic!("Unknown chain {} in checkpoints - data corrupted", other),
+    }
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("Failed to serialize row")
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(json: &str) -> T {
+    serde_json::from_str(json).expect("Failed to parse row - data corrupted")
+}
+
+impl StateBackend for SqliteBackend {
+    fn load_state(&self) -> RouteState {
+        let conn = self.conn.locked();
+        let mut state = conn.query_row(
+            "SELECT inbox_last_block, outbox_last_block, indexing_since, on_sync FROM route_state WHERE id = 0",
+            [],
+            |r| Ok(RouteState {
+                inbox_last_block: r.get::<_, Option<i64>>(0)?.map(|v| v as u64),
+                outbox_last_block: r.get::<_, Option<i64>>(1)?.map(|v| v as u64),
+                tasks: Vec::new(),
+                indexing_since: r.get::<_, Option<i64>>(2)?.map(|v| v as u64),
+                on_sync: r.get(3)?,
+                dead_letters: Vec::new(),
+                missed_deadlines: Vec::new(),
+                checkpoints: Vec::new(),
+                pending_undo: Vec::new(),
+            }),
+        ).expect("Failed to read route state");
+
+        let mut stmt = conn.prepare("SELECT task FROM tasks ORDER BY id").expect("Failed to query tasks");
+        state.tasks = stmt.query_map([], |r| r.get::<_, String>(0))
+            .expect("Failed to query tasks")
+            .map(|row| from_json(&row.expect("Failed to read task row")))
+            .collect();
+
+        let mut stmt = conn.prepare("SELECT task FROM dead_letters ORDER BY id").expect("Failed to query dead letters");
+        state.dead_letters = stmt.query_map([], |r| r.get::<_, String>(0))
+            .expect("Failed to query dead letters")
+            .map(|row| from_json(&row.expect("Failed to read dead letter row")))
+            .collect();
+
+        let mut stmt = conn.prepare("SELECT epoch, kind, deadline, detected_at FROM missed_deadlines ORDER BY deadline")
+            .expect("Failed to query missed deadlines");
+        state.missed_deadlines = stmt.query_map([], |r| Ok(MissedDeadline {
+                epoch: r.get::<_, i64>(0)? as u64,
+                kind: r.get(1)?,
+                deadline: r.get::<_, i64>(2)? as u64,
+                detected_at: r.get::<_, i64>(3)? as u64,
+            }))
+            .expect("Failed to query missed deadlines")
+            .map(|row| row.expect("Failed to read missed deadline row"))
+            .collect();
+
+        let mut stmt = conn.prepare("SELECT checkpoint FROM checkpoints ORDER BY id").expect("Failed to query checkpoints");
+        state.checkpoints = stmt.query_map([], |r| r.get::<_, String>(0))
+            .expect("Failed to query checkpoints")
+            .map(|row| from_json(&row.expect("Failed to read checkpoint row")))
+            .collect();
+
+        let mut stmt = conn.prepare("SELECT chain, undo FROM pending_undo ORDER BY id").expect("Failed to query pending undo");
+        state.pending_undo = stmt.query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)))
+            .expect("Failed to query pending undo")
+            .map(|row| {
+                let (chain, undo) = row.expect("Failed to read pending undo row");
+                (indexed_chain(&chain), from_json(&undo))
+            })
+            .collect();
+        state
+    }
+
+    fn load_claims(&self) -> Vec<ClaimData> {
+        let conn = self.conn.locked();
+        let mut stmt = conn.prepare("SELECT claim FROM claims ORDER BY epoch").expect("Failed to query claims");
+        stmt.query_map([], |r| r.get::<_, String>(0))
+            .expect("Failed to query claims")
+            .map(|row| from_json(&row.expect("Failed to read claim row")))
+            .collect()
+    }
+
+    fn get_claim(&self, epoch: u64) -> Option<ClaimData> {
+        let conn = self.conn.locked();
+        conn.query_row("SELECT claim FROM claims WHERE epoch = ?1", params![epoch as i64], |r| r.get::<_, String>(0))
+            .optional()
+            .expect("Failed to query claim")
+            .map(|json| from_json(&json))
+    }
+
+    fn apply(&self, ops: &[StoreOp]) {
+        let mut conn = self.conn.locked();
+        let tx = conn.transaction().expect("Failed to begin transaction");
+        for op in ops {
+            Self::apply_op(&tx, op).expect("Failed to apply state update");
+        }
+        tx.commit().expect("Failed to commit transaction");
+    }
+}
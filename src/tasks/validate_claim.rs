@@ -1,8 +1,11 @@
 use alloy::primitives::U256;
 use std::sync::{Arc, Mutex};
+use crate::alerts::{self, Alert, AlertKind};
 use crate::config::Route;
 use crate::contracts::IVeaInbox;
-use crate::tasks::{Task, TaskKind, TaskStore, ClaimStore};
+use crate::tasks::{Task, TaskError, TaskKind, TaskStore, ClaimStore};
+use tracing::{info, warn};
+use crate::sync::LockExt;
 
 pub async fn execute(
     route: &Route,
@@ -10,28 +13,25 @@ pub async fn execute(
     claim_store: &Arc<Mutex<ClaimStore>>,
     current_timestamp: u64,
     task_store: &Arc<Mutex<TaskStore>>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let claim_data = claim_store.lock().unwrap().get(epoch);
+) -> Result<(), TaskError> {
+    let claim_data = claim_store.locked().get(epoch);
     let claimed_state_root = claim_data.state_root;
 
     let inbox = IVeaInbox::new(route.inbox_address, route.inbox_provider.clone());
     let correct_state_root = inbox.snapshots(U256::from(epoch)).call().await?;
 
     if claimed_state_root == correct_state_root {
-        println!("[{}][task::validate_claim] Epoch {} VALID", route.name, epoch);
-        task_store.lock().unwrap().add_task(Task {
-            epoch,
-            execute_after: current_timestamp + route.settings.start_verification_delay,
-            kind: TaskKind::StartVerification,
-        });
+        info!(epoch, "Claim VALID");
+        let execute_after = current_timestamp + route.settings.start_verification_delay;
+        task_store.locked().add_task(Task::new(epoch, execute_after, TaskKind::StartVerification));
     } else {
-        println!("[{}][task::validate_claim] Epoch {} INVALID - scheduling challenge", route.name, epoch);
-        println!("[{}][task::validate_claim] Claimed: {:?}, Correct: {:?}", route.name, claimed_state_root, correct_state_root);
-        task_store.lock().unwrap().add_task(Task {
-            epoch,
-            execute_after: current_timestamp,
-            kind: TaskKind::Challenge,
-        });
+        warn!(epoch, claimed = %claimed_state_root, correct = %correct_state_root, "Claim INVALID - scheduling challenge");
+        let deadline = route.settings.challenge_deadline(claim_data.timestamp_claimed as u64);
+        alerts::fire(Alert::new(AlertKind::InvalidClaim, &route.name, epoch, format!(
+            "claim for epoch {} by {} has state root {}, inbox snapshot is {}; challenge deadline {}",
+            epoch, claim_data.claimer, claimed_state_root, correct_state_root, deadline,
+        )));
+        task_store.locked().add_task(Task::new(epoch, current_timestamp, TaskKind::Challenge).with_deadline(deadline));
     }
 
     Ok(())
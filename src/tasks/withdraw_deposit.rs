@@ -2,55 +2,61 @@ use alloy::primitives::{FixedBytes, U256};
 use std::sync::{Arc, Mutex};
 use crate::config::Route;
 use crate::contracts::{IVeaOutbox, Party};
-use crate::tasks::{send_tx, ClaimStore};
+use crate::tasks::fees::FeeBid;
+use crate::tasks::{send_tx, ClaimStore, TaskError};
+use tracing::info;
+use crate::sync::LockExt;
 
 pub async fn execute(
     route: &Route,
     epoch: u64,
     claim_store: &Arc<Mutex<ClaimStore>>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    bid: FeeBid,
+) -> Result<(), TaskError> {
     let outbox = IVeaOutbox::new(route.outbox_address, route.outbox_provider.clone());
 
     let claim_hash = outbox.claimHashes(U256::from(epoch)).call().await?;
     if claim_hash == FixedBytes::<32>::ZERO {
-        println!("[{}][task::withdraw_deposit] Epoch {} already withdrawn", route.name, epoch);
-        claim_store.lock().unwrap().remove(epoch);
+        info!(epoch, "Already withdrawn");
+        claim_store.locked().remove(epoch);
         return Ok(());
     }
 
-    let claim = claim_store.lock().unwrap().get_claim(epoch);
-    println!("[{}][task::withdraw_deposit] Epoch {} - {:?} was honest, withdrawing deposit", route.name, epoch, claim.honest);
+    let claim = claim_store.locked().get_claim(epoch);
+    info!(epoch, honest = ?claim.honest, "Withdrawing deposit");
 
     let result = match claim.honest {
         Party::Claimer => {
             send_tx(
-                outbox.withdrawClaimDeposit(U256::from(epoch), claim).send().await,
+                outbox.withdrawClaimDeposit(U256::from(epoch), claim),
+                &route.outbox_nonces,
+                bid,
                 "withdrawClaimDeposit",
-                route.name,
-                &[],
+                &route.name,
             ).await
         }
         Party::Challenger => {
             send_tx(
-                outbox.withdrawChallengeDeposit(U256::from(epoch), claim).send().await,
+                outbox.withdrawChallengeDeposit(U256::from(epoch), claim),
+                &route.outbox_nonces,
+                bid,
                 "withdrawChallengeDeposit",
-                route.name,
-                &[],
+                &route.name,
             ).await
         }
-        _ => panic!("Cannot withdraw - honest party not determined for epoch {}", epoch),
+        _ => return Err(TaskError::Fatal(format!("honest party not determined for epoch {}", epoch))),
     };
 
     if let Err(e) = result {
         let claim_hash = outbox.claimHashes(U256::from(epoch)).call().await?;
         if claim_hash == FixedBytes::<32>::ZERO {
-            println!("[{}][task::withdraw_deposit] Epoch {} already withdrawn by another validator", route.name, epoch);
-            claim_store.lock().unwrap().remove(epoch);
-            return Ok(());
+            info!(epoch, "Already withdrawn by another validator");
+            claim_store.locked().remove(epoch);
+            return Err(TaskError::RaceLost);
         }
         return Err(e);
     }
 
-    claim_store.lock().unwrap().remove(epoch);
+    claim_store.locked().remove(epoch);
     Ok(())
 }
@@ -0,0 +1,115 @@
+mod common;
+
+use alloy::primitives::{Address, FixedBytes};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use vea_validator::admin::{self, AdminState};
+use vea_validator::tasks::storage::{open_route_stores, StorageKind};
+use vea_validator::tasks::{ClaimData, Task, TaskKind, TaskStore};
+use common::{free_addr, http_request};
+
+fn setup(dir: &std::path::Path) -> (AdminState, Arc<Mutex<TaskStore>>, Arc<AtomicBool>) {
+    let (task_store, claim_store) = open_route_stores(StorageKind::Json, dir, "ARB_TO_ETH");
+    task_store.add_task(Task::new(5, 1000, TaskKind::Challenge));
+    task_store.add_task(Task::new(6, 2000, TaskKind::WithdrawDeposit));
+    claim_store.store(ClaimData {
+        epoch: 5,
+        state_root: FixedBytes::<32>::from([0xAB; 32]),
+        claimer: Address::repeat_byte(0x11),
+        timestamp_claimed: 1000,
+        timestamp_verification: 0,
+        blocknumber_verification: 0,
+        honest: "None".to_string(),
+        challenger: Address::ZERO,
+    });
+
+    let task_store = Arc::new(Mutex::new(task_store));
+    let make_claims = Arc::new(AtomicBool::new(false));
+    let mut state = AdminState::new(make_claims.clone());
+    state.add_route("ARB_TO_ETH", task_store.clone(), Arc::new(Mutex::new(claim_store)));
+    (state, task_store, make_claims)
+}
+
+fn call(state: &AdminState, method: &str, params: Value) -> Value {
+    let request = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+    serde_json::from_str(&state.handle_request(&request.to_string())).unwrap()
+}
+
+#[test]
+fn test_admin_manages_tasks_through_shared_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let (state, task_store, _) = setup(dir.path());
+
+    let tasks = call(&state, "tasks_list", json!({"route": "ARB_TO_ETH"}));
+    assert_eq!(tasks["result"].as_array().unwrap().len(), 2);
+
+    call(&state, "tasks_run_now", json!({"route": "ARB_TO_ETH", "epoch": 5, "kind": "Challenge"}));
+    call(&state, "tasks_reschedule", json!({"route": "ARB_TO_ETH", "epoch": 6, "kind": "WithdrawDeposit", "execute_after": 4242}));
+    let tasks = task_store.lock().unwrap().load().tasks;
+    assert_eq!(tasks.iter().find(|t| t.epoch == 5).unwrap().execute_after, 0);
+    assert_eq!(tasks.iter().find(|t| t.epoch == 6).unwrap().execute_after, 4242);
+
+    call(&state, "tasks_drop", json!({"route": "ARB_TO_ETH", "epoch": 5, "kind": "Challenge"}));
+    let tasks = task_store.lock().unwrap().load().tasks;
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].epoch, 6);
+
+    let missing = call(&state, "tasks_drop", json!({"route": "ARB_TO_ETH", "epoch": 5, "kind": "Challenge"}));
+    assert_eq!(missing["error"]["code"], -32000);
+}
+
+#[test]
+fn test_admin_lists_and_requeues_dead_letters() {
+    let dir = tempfile::tempdir().unwrap();
+    let (state, task_store, _) = setup(dir.path());
+    let task = task_store.lock().unwrap().find_task(6, "WithdrawDeposit").unwrap();
+    task_store.lock().unwrap().dead_letter(&task, "fatal: no honest party");
+
+    let dead = call(&state, "dead_letters_list", json!({"route": "ARB_TO_ETH"}));
+    assert_eq!(dead["result"][0]["last_error"], "fatal: no honest party");
+    assert_eq!(call(&state, "routes_list", Value::Null)["result"][0]["dead_letters"], 1);
+
+    call(&state, "dead_letters_requeue", json!({"route": "ARB_TO_ETH", "epoch": 6, "kind": "WithdrawDeposit"}));
+    let requeued = task_store.lock().unwrap().find_task(6, "WithdrawDeposit").unwrap();
+    assert_eq!((requeued.execute_after, requeued.attempts), (0, 0));
+
+    let missing = call(&state, "dead_letters_drop", json!({"route": "ARB_TO_ETH", "epoch": 6, "kind": "WithdrawDeposit"}));
+    assert_eq!(missing["error"]["code"], -32000);
+}
+
+#[test]
+fn test_admin_claims_and_make_claims_toggle() {
+    let dir = tempfile::tempdir().unwrap();
+    let (state, _, make_claims) = setup(dir.path());
+
+    let claim = call(&state, "claims_get", json!({"route": "ARB_TO_ETH", "epoch": 5}));
+    assert_eq!(claim["result"]["claimer"], json!(Address::repeat_byte(0x11)));
+    let none = call(&state, "claims_get", json!({"route": "ARB_TO_ETH", "epoch": 99}));
+    assert!(none["result"].is_null());
+
+    call(&state, "make_claims_set", json!({"enabled": true}));
+    assert!(make_claims.load(Ordering::Relaxed));
+    assert_eq!(call(&state, "make_claims_get", Value::Null)["result"], json!(true));
+
+    assert_eq!(call(&state, "nope", Value::Null)["error"]["code"], -32601);
+    assert_eq!(call(&state, "tasks_list", json!({}))["error"]["code"], -32602);
+    assert_eq!(call(&state, "tasks_list", json!({"route": "OTHER"}))["error"]["code"], -32000);
+}
+
+#[tokio::test]
+async fn test_admin_serves_json_rpc_over_http() {
+    let dir = tempfile::tempdir().unwrap();
+    let (state, _, _) = setup(dir.path());
+    let addr = free_addr();
+    tokio::spawn(admin::serve(addr, Arc::new(state)));
+
+    let body = json!({"jsonrpc": "2.0", "id": 7, "method": "routes_list"}).to_string();
+    let response = http_request(addr, &format!("POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", body.len(), body)).await;
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    let json: Value = serde_json::from_str(response.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+    assert_eq!(json["id"], 7);
+    assert_eq!(json["result"][0]["route"], "ARB_TO_ETH");
+    assert_eq!(json["result"][0]["pending_tasks"], 2);
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use vea_validator::alerts::{Alert, AlertConfig, AlertKind, Alerter, SinkConfig};
+use common::{example_config, read_http_request, validate_err};
+
+fn alerter(sinks: Vec<SinkConfig>) -> Alerter {
+    Alerter::new(&AlertConfig { sinks, dedup_secs: 3600 })
+}
+
+#[tokio::test]
+async fn test_file_sink_deduplicates_identical_alerts() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("alerts").join("alerts.jsonl");
+    let alerter = alerter(vec![SinkConfig::File { path: path.clone() }]);
+
+    let invalid = |epoch: u64| Alert::new(AlertKind::InvalidClaim, "ARB_TO_ETH", epoch, "bad state root");
+    assert!(alerter.send(&invalid(7)).await);
+    assert!(!alerter.send(&invalid(7)).await);
+    assert!(alerter.send(&invalid(8)).await);
+    assert!(alerter.send(&Alert::new(AlertKind::InvalidClaim, "ARB_TO_GNOSIS", 7, "bad state root")).await);
+
+    let lines: Vec<serde_json::Value> = std::fs::read_to_string(&path).unwrap()
+        .lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["kind"], "invalid_claim");
+    assert_eq!(lines[0]["severity"], "critical");
+    assert_eq!(lines[0]["subject"], "7");
+    assert_eq!(lines[2]["route"], "ARB_TO_GNOSIS");
+}
+
+#[tokio::test]
+async fn test_alert_repeats_after_dedup_window() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("alerts.jsonl");
+    let alerter = Alerter::new(&AlertConfig { sinks: vec![SinkConfig::File { path: path.clone() }], dedup_secs: 60 });
+
+    let mut alert = Alert::new(AlertKind::SyncStalled, "ARB_TO_ETH", "indexer", "stalled");
+    assert!(alerter.send(&alert).await);
+    alert.timestamp += 30;
+    assert!(!alerter.send(&alert).await);
+    alert.timestamp += 31;
+    assert!(alerter.send(&alert).await);
+    assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+}
+
+#[tokio::test]
+async fn test_webhook_sink_posts_json() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let request = read_http_request(&mut socket).await;
+        socket.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n").await.unwrap();
+        request
+    });
+
+    let alerter = alerter(vec![SinkConfig::Webhook { url }]);
+    assert!(alerter.send(&Alert::new(AlertKind::DeadLetter, "ARB_TO_ETH", "9 Challenge", "dead-lettered")).await);
+
+    let (head, body) = server.await.unwrap();
+    assert!(head.starts_with("POST /hook"));
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["kind"], "dead_letter");
+    assert_eq!(body["severity"], "warning");
+}
+
+#[tokio::test]
+async fn test_smtp_sink_speaks_to_relay() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let relay = listener.local_addr().unwrap().to_string();
+    let server = tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut reader = BufReader::new(socket);
+        let mut transcript = Vec::new();
+        reader.get_mut().write_all(b"220 relay ready\r\n").await.unwrap();
+        let mut in_data = false;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).await.unwrap() == 0 {
+                break;
+            }
+            let line = line.trim_end().to_string();
+            transcript.push(line.clone());
+            let reply: &[u8] = if in_data {
+                if line != "." {
+                    continue;
+                }
+                in_data = false;
+                b"250 queued\r\n"
+            } else if line == "DATA" {
+                in_data = true;
+                b"354 go ahead\r\n"
+            } else if line == "QUIT" {
+                reader.get_mut().write_all(b"221 bye\r\n").await.unwrap();
+                break;
+            } else {
+                b"250 ok\r\n"
+            };
+            reader.get_mut().write_all(reply).await.unwrap();
+        }
+        transcript
+    });
+
+    let alerter = alerter(vec![SinkConfig::Smtp {
+        relay,
+        from: "validator@example.org".to_string(),
+        to: vec!["oncall@example.org".to_string()],
+    }]);
+    assert!(alerter.send(&Alert::new(AlertKind::ChallengeFailed, "ARB_TO_ETH", 3, "reverted")).await);
+
+    let transcript = server.await.unwrap();
+    assert_eq!(transcript[0], "HELO vea-validator");
+    assert_eq!(transcript[1], "MAIL FROM:<validator@example.org>");
+    assert_eq!(transcript[2], "RCPT TO:<oncall@example.org>");
+    assert_eq!(transcript[3], "DATA");
+    assert!(transcript.iter().any(|l| l.starts_with("Subject: [critical] ARB_TO_ETH challenge_failed")));
+    assert_eq!(transcript.last().unwrap(), "QUIT");
+}
+
+#[tokio::test]
+async fn test_failing_sink_does_not_block_others() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("alerts.jsonl");
+    let alerter = alerter(vec![SinkConfig::Webhook { url: "http://127.0.0.1:1/".to_string() }])
+        .with_sink(SinkConfig::File { path: path.clone() }.build());
+    assert!(alerter.send(&Alert::new(AlertKind::LowBalance, "ARB_TO_ETH", "Ethereum", "balance low")).await);
+    assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+}
+
+#[test]
+fn test_alert_sinks_in_config() {
+    let mut file = example_config();
+    assert_eq!(file.alerts.dedup_secs, 3600);
+    assert!(matches!(file.alerts.sinks[0], SinkConfig::Stdout));
+    file.validate().unwrap();
+
+    file.alerts.sinks.push(SinkConfig::Webhook { url: "hooks.example.org".to_string() });
+    assert_eq!(validate_err(&file), "Invalid alert webhook URL: hooks.example.org");
+    file.alerts.sinks.pop();
+
+    file.alerts.sinks.push(SinkConfig::Smtp { relay: "127.0.0.1:25".to_string(), from: "a@b".to_string(), to: Vec::new() });
+    assert_eq!(validate_err(&file), "SMTP alert sink needs at least one recipient");
+}
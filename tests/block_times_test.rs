@@ -0,0 +1,107 @@
+mod common;
+
+use alloy::providers::{Provider, ProviderBuilder};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use vea_validator::block_times::BlockTimes;
+use vea_validator::tasks::TaskError;
+use common::mock_json_rpc;
+
+const LATEST: u64 = 1_000_000;
+
+/// Several blocks per second for the first half, one every 3 seconds after.
+fn timestamp(block: u64) -> u64 {
+    if block <= LATEST / 2 {
+        1_700_000_000 + block * 4 / 10
+    } else {
+        1_700_000_000 + (LATEST / 2) * 4 / 10 + (block - LATEST / 2) * 3
+    }
+}
+
+fn block(number: u64) -> Value {
+    let zero = format!("0x{}", "00".repeat(32));
+    json!({
+        "hash": format!("0x{:064x}", number + 1),
+        "parentHash": zero,
+        "sha3Uncles": zero,
+        "miner": format!("0x{}", "00".repeat(20)),
+        "stateRoot": zero,
+        "transactionsRoot": zero,
+        "receiptsRoot": zero,
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "difficulty": "0x0",
+        "number": format!("0x{:x}", number),
+        "gasLimit": "0x1c9c380",
+        "gasUsed": "0x0",
+        "timestamp": format!("0x{:x}", timestamp(number)),
+        "extraData": "0x",
+        "mixHash": zero,
+        "nonce": "0x0000000000000000",
+        "uncles": [],
+        "transactions": [],
+    })
+}
+
+fn respond(method: &str, params: &Value, block_fetches: &AtomicUsize) -> Option<Value> {
+    Some(match method {
+        "eth_blockNumber" => json!(format!("0x{:x}", LATEST)),
+        "eth_chainId" => json!("0x1"),
+        "eth_getBlockByNumber" => {
+            block_fetches.fetch_add(1, Ordering::SeqCst);
+            let number = u64::from_str_radix(params[0].as_str().unwrap().trim_start_matches("0x"), 16).unwrap();
+            if number > LATEST { Value::Null } else { block(number) }
+        }
+        _ => return None,
+    })
+}
+
+/// A node serving blocks up to `LATEST`, counting the blocks fetched.
+async fn mock_node(block_fetches: Arc<AtomicUsize>) -> String {
+    mock_json_rpc(move |method, params| respond(method, params, &block_fetches)).await
+}
+
+fn first_block_at(target_ts: u64) -> u64 {
+    let (mut lo, mut hi) = (0, LATEST);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if timestamp(mid) < target_ts { lo = mid + 1 } else { hi = mid }
+    }
+    lo
+}
+
+#[tokio::test]
+async fn test_find_block_interpolates_and_caches() {
+    let block_fetches = Arc::new(AtomicUsize::new(0));
+    let url = mock_node(block_fetches.clone()).await;
+    let provider = ProviderBuilder::new().connect_http(url.parse().unwrap()).erased();
+    let blocks = BlockTimes::new("Test", provider, 3000);
+
+    let head = timestamp(LATEST);
+    for target in [head - 15 * 60, head - 7 * 24 * 3600, timestamp(LATEST / 4) + 1, timestamp(0), 0, head + 60] {
+        block_fetches.store(0, Ordering::SeqCst);
+        assert_eq!(blocks.find_block(target).await.unwrap(), first_block_at(target).min(LATEST), "target {}", target);
+        assert!(block_fetches.load(Ordering::SeqCst) <= 10, "target {} took {} fetches", target, block_fetches.load(Ordering::SeqCst));
+    }
+
+    block_fetches.store(0, Ordering::SeqCst);
+    assert_eq!(blocks.find_block(head - 15 * 60).await.unwrap(), first_block_at(head - 15 * 60));
+    assert_eq!(block_fetches.load(Ordering::SeqCst), 0, "repeated search served from cache");
+
+    assert_eq!(blocks.timestamp(123).await.unwrap(), timestamp(123));
+    assert_eq!(blocks.timestamp(123).await.unwrap(), timestamp(123));
+    assert_eq!(block_fetches.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_lookup_failures_are_errors() {
+    let url = mock_node(Arc::new(AtomicUsize::new(0))).await;
+    let provider = ProviderBuilder::new().connect_http(url.parse().unwrap()).erased();
+    let blocks = BlockTimes::new("Test", provider, 3000);
+    assert!(matches!(blocks.timestamp(LATEST + 1).await, Err(TaskError::RpcUnavailable(_))));
+
+    let url = mock_json_rpc(|_, _| None).await;
+    let provider = ProviderBuilder::new().connect_http(url.parse().unwrap()).erased();
+    let blocks = BlockTimes::new("Test", provider, 3000);
+    assert!(blocks.find_block(0).await.is_err());
+}
@@ -8,7 +8,6 @@ use vea_validator::{
     config::ValidatorConfig,
     indexer::EventIndexer,
     tasks::{dispatcher::TaskDispatcher, TaskStore, ClaimStore},
-    startup::ensure_weth_approval,
 };
 use std::str::FromStr;
 use common::{restore_pristine, advance_time, send_messages};
@@ -48,7 +47,7 @@ async fn test_send_snapshot_after_challenge() {
     let claim_store = Arc::new(Mutex::new(ClaimStore::new(&claims_path)));
     let wallet_address = c.wallet.default_signer().address();
     let indexer = EventIndexer::new(route.clone(), wallet_address, task_store.clone(), claim_store.clone());
-    indexer.initialize().await;
+    indexer.initialize().await.unwrap();
     task_store.lock().unwrap().set_on_sync(true);
     let dispatcher = TaskDispatcher::new(c.clone(), route.clone(), task_store, claim_store);
 
@@ -100,7 +99,7 @@ async fn test_send_snapshot_on_challenged_event() {
     let claim_store = Arc::new(Mutex::new(ClaimStore::new(&claims_path)));
     let wallet_address = c.wallet.default_signer().address();
     let indexer = EventIndexer::new(route.clone(), wallet_address, task_store.clone(), claim_store.clone());
-    indexer.initialize().await;
+    indexer.initialize().await.unwrap();
     task_store.lock().unwrap().set_on_sync(true);
     let dispatcher = TaskDispatcher::new(c.clone(), route.clone(), task_store, claim_store);
 
@@ -178,7 +177,7 @@ async fn test_execute_relay() {
     let claim_store = Arc::new(Mutex::new(ClaimStore::new(&claims_path)));
     let wallet_address = c.wallet.default_signer().address();
     let indexer = EventIndexer::new(route.clone(), wallet_address, task_store.clone(), claim_store.clone());
-    indexer.initialize().await;
+    indexer.initialize().await.unwrap();
     task_store.lock().unwrap().set_on_sync(true);
     let dispatcher = TaskDispatcher::new(c.clone(), route.clone(), task_store, claim_store.clone());
 
@@ -218,7 +217,7 @@ async fn test_send_snapshot_gnosis() {
 
     let weth = IWETH::new(route.weth_address.unwrap(), outbox_provider.clone());
     weth.deposit().value(U256::from(10u64).pow(U256::from(19))).send().await.unwrap().get_receipt().await.unwrap();
-    ensure_weth_approval(&c, route.outbox_provider.clone(), wallet_address).await.unwrap();
+    route.adapter.prepare_deposit(route).await.unwrap();
 
     let inbox = IVeaInboxArbToGnosis::new(route.inbox_address, route.inbox_provider.clone());
     let outbox = IVeaOutboxArbToGnosis::new(route.outbox_address, outbox_provider.clone());
@@ -249,7 +248,7 @@ async fn test_send_snapshot_gnosis() {
     let task_store = Arc::new(Mutex::new(TaskStore::new(&schedule_path)));
     let claim_store = Arc::new(Mutex::new(ClaimStore::new(&claims_path)));
     let indexer = EventIndexer::new(route.clone(), wallet_address, task_store.clone(), claim_store.clone());
-    indexer.initialize().await;
+    indexer.initialize().await.unwrap();
     task_store.lock().unwrap().set_on_sync(true);
     let dispatcher = TaskDispatcher::new(c.clone(), route.clone(), task_store, claim_store);
 
@@ -316,7 +315,7 @@ async fn test_execute_relay_skips_spent() {
     let claim_store = Arc::new(Mutex::new(ClaimStore::new(&claims_path)));
     let wallet_address = c.wallet.default_signer().address();
     let indexer = EventIndexer::new(route.clone(), wallet_address, task_store.clone(), claim_store.clone());
-    indexer.initialize().await;
+    indexer.initialize().await.unwrap();
     task_store.lock().unwrap().set_on_sync(true);
     let dispatcher = TaskDispatcher::new(c.clone(), route.clone(), task_store.clone(), claim_store.clone());
 
@@ -370,7 +369,7 @@ async fn test_challenger_wins_bad_claim() {
     let claim_store = Arc::new(Mutex::new(ClaimStore::new(&claims_path)));
     let wallet_address = c.wallet.default_signer().address();
     let indexer = EventIndexer::new(route.clone(), wallet_address, task_store.clone(), claim_store.clone());
-    indexer.initialize().await;
+    indexer.initialize().await.unwrap();
     task_store.lock().unwrap().set_on_sync(true);
     let dispatcher = TaskDispatcher::new(c.clone(), route.clone(), task_store, claim_store);
 
@@ -8,7 +8,6 @@ use vea_validator::{
     config::ValidatorConfig,
     indexer::EventIndexer,
     tasks::{dispatcher::TaskDispatcher, TaskStore, ClaimStore},
-    startup::ensure_weth_approval,
 };
 use common::{restore_pristine, advance_time, send_messages};
 use alloy::providers::Provider;
@@ -47,7 +46,7 @@ async fn test_challenge_bad_claim() {
     let claim_store = Arc::new(Mutex::new(ClaimStore::new(&claims_path)));
     let wallet_address = c.wallet.default_signer().address();
     let indexer = EventIndexer::new(route.clone(), wallet_address, task_store.clone(), claim_store.clone());
-    indexer.initialize().await;
+    indexer.initialize().await.unwrap();
     task_store.lock().unwrap().set_on_sync(true);
     let dispatcher = TaskDispatcher::new(c.clone(), route.clone(), task_store, claim_store);
 
@@ -73,7 +72,7 @@ async fn test_challenge_bad_claim_gnosis() {
 
     let weth = IWETH::new(route.weth_address.unwrap(), outbox_provider.clone());
     weth.deposit().value(U256::from(10u64).pow(U256::from(19))).send().await.unwrap().get_receipt().await.unwrap();
-    ensure_weth_approval(&c, route.outbox_provider.clone(), wallet_address).await.unwrap();
+    route.adapter.prepare_deposit(route).await.unwrap();
 
     let inbox = IVeaInboxArbToGnosis::new(route.inbox_address, route.inbox_provider.clone());
     let outbox = IVeaOutboxArbToGnosis::new(route.outbox_address, outbox_provider.clone());
@@ -104,7 +103,7 @@ async fn test_challenge_bad_claim_gnosis() {
     let task_store = Arc::new(Mutex::new(TaskStore::new(&schedule_path)));
     let claim_store = Arc::new(Mutex::new(ClaimStore::new(&claims_path)));
     let indexer = EventIndexer::new(route.clone(), wallet_address, task_store.clone(), claim_store.clone());
-    indexer.initialize().await;
+    indexer.initialize().await.unwrap();
     task_store.lock().unwrap().set_on_sync(true);
     let dispatcher = TaskDispatcher::new(c.clone(), route.clone(), task_store, claim_store);
 
@@ -135,7 +134,7 @@ async fn test_weth_approval_set_on_startup() {
     }
     assert_eq!(weth.allowance(wallet_address, route.outbox_address).call().await.unwrap(), U256::ZERO);
 
-    ensure_weth_approval(&c, route.outbox_provider.clone(), wallet_address).await.unwrap();
+    route.adapter.prepare_deposit(route).await.unwrap();
     assert_eq!(weth.allowance(wallet_address, route.outbox_address).call().await.unwrap(), U256::MAX);
 }
 
@@ -153,7 +152,7 @@ async fn test_weth_approval_skipped_if_exists() {
     let manual = U256::from(1000000000u64);
     weth.approve(route.outbox_address, manual).from(wallet_address).send().await.unwrap().get_receipt().await.unwrap();
 
-    ensure_weth_approval(&c, route.outbox_provider.clone(), wallet_address).await.unwrap();
+    route.adapter.prepare_deposit(route).await.unwrap();
     assert_eq!(weth.allowance(wallet_address, route.outbox_address).call().await.unwrap(), manual);
 }
 
@@ -194,7 +193,7 @@ async fn test_start_verification_drops_task_when_claim_challenged() {
     let claim_store = Arc::new(Mutex::new(ClaimStore::new(&claims_path)));
     let wallet_address = c.wallet.default_signer().address();
     let indexer = EventIndexer::new(route.clone(), wallet_address, task_store.clone(), claim_store.clone());
-    indexer.initialize().await;
+    indexer.initialize().await.unwrap();
     task_store.lock().unwrap().set_on_sync(true);
 
     indexer.scan_once().await;
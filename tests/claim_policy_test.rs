@@ -0,0 +1,32 @@
+use vea_validator::tasks::claim_policy::{ClaimMode, ClaimPolicy};
+
+const GWEI: u128 = 1_000_000_000;
+
+#[test]
+fn test_cost_covers_the_whole_claim_lifecycle() {
+    let policy = ClaimPolicy::default();
+    let follow_up = policy.start_verification_gas + policy.verify_snapshot_gas + policy.withdraw_gas;
+    assert_eq!(policy.cost(90_000, 10 * GWEI), (90_000 + follow_up) as u128 * 10 * GWEI);
+}
+
+#[test]
+fn test_profitable_only_when_reward_covers_cost_and_margin() {
+    let policy = ClaimPolicy {
+        mode: ClaimMode::IfProfitable,
+        reward_wei: 1_000_000 * GWEI,
+        min_profit_wei: 100_000 * GWEI,
+        ..ClaimPolicy::default()
+    };
+    assert!(policy.is_profitable(900_000 * GWEI));
+    assert!(!policy.is_profitable(900_001 * GWEI));
+    assert!(!ClaimPolicy::default().is_profitable(1));
+    assert!(ClaimPolicy::default().is_profitable(0));
+}
+
+#[test]
+fn test_only_if_unclaimed_delays_claims() {
+    let waiting = ClaimPolicy { mode: ClaimMode::IfUnclaimed, wait_secs: 3600, ..ClaimPolicy::default() };
+    assert_eq!(waiting.claim_delay(), 3600);
+    assert_eq!(ClaimPolicy { mode: ClaimMode::IfProfitable, ..waiting }.claim_delay(), 0);
+    assert_eq!(ClaimPolicy::default().claim_delay(), 0);
+}
@@ -0,0 +1,29 @@
+use clap::Parser;
+use vea_validator::cli::{Cli, ClaimsCommand, Command, TasksCommand};
+
+#[test]
+fn test_cli_defaults_to_run_mode() {
+    let cli = Cli::try_parse_from(["vea-validator"]).unwrap();
+    assert!(cli.command.is_none());
+    assert_eq!(cli.data_dir.to_str(), Some("data"));
+}
+
+#[test]
+fn test_cli_parses_subcommands() {
+    let cli = Cli::try_parse_from(["vea-validator", "tasks", "reschedule", "--route", "ARB_TO_ETH", "12", "Challenge", "1700000000", "--data-dir", "/tmp/x"]).unwrap();
+    assert_eq!(cli.data_dir.to_str(), Some("/tmp/x"));
+    match cli.command {
+        Some(Command::Tasks { action: TasksCommand::Reschedule { route, epoch, kind, execute_after } }) => {
+            assert_eq!((route.as_str(), epoch, kind.as_str(), execute_after), ("ARB_TO_ETH", 12, "Challenge", 1700000000));
+        }
+        _ => panic!("expected tasks reschedule"),
+    }
+
+    let cli = Cli::try_parse_from(["vea-validator", "claims", "show", "7"]).unwrap();
+    assert!(matches!(cli.command, Some(Command::Claims { action: ClaimsCommand::Show { epoch: 7, route: None } })));
+
+    let cli = Cli::try_parse_from(["vea-validator", "resync", "--from", "1700000000", "--route", "ARB_TO_GNOSIS"]).unwrap();
+    assert!(matches!(cli.command, Some(Command::Resync { from: 1700000000, route: Some(_) })));
+
+    assert!(Cli::try_parse_from(["vea-validator", "resync"]).is_err());
+}
@@ -1,11 +1,97 @@
+// Each test crate uses a different subset of these helpers.
+#![allow(dead_code)]
+
 pub use alloy::providers::Provider;
 use alloy::providers::ProviderBuilder;
 use alloy::primitives::Address;
-use vea_validator::config::Route;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use vea_validator::config::{ConfigFile, Route};
 use vea_validator::contracts::IVeaInboxArbToEth;
 use std::str::FromStr;
 
 const SNAPSHOT_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/.devnet-snapshot");
+pub const EXAMPLE_CONFIG: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/config.example.json");
+
+pub fn example_config() -> ConfigFile {
+    ConfigFile::load(EXAMPLE_CONFIG).unwrap()
+}
+
+/// The message `file` fails validation with.
+pub fn validate_err(file: &ConfigFile) -> String {
+    file.validate().unwrap_err().to_string()
+}
+
+/// Reads one HTTP request off `socket`, returning its head and body.
+pub async fn read_http_request(socket: &mut TcpStream) -> (String, Vec<u8>) {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = socket.read(&mut buf).await.unwrap();
+        request.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&request);
+        if let Some(end) = text.find("\r\n\r\n") {
+            let length: usize = text.lines()
+                .find_map(|l| l.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap()))
+                .unwrap_or(0);
+            if request.len() >= end + 4 + length {
+                let head = text[..end].to_string();
+                return (head, request[end + 4..end + 4 + length].to_vec());
+            }
+        }
+    }
+}
+
+/// A JSON-RPC node answering each call with `respond(method, params)`, one
+/// connection per request. `None` answers "method not found".
+pub async fn mock_json_rpc<F>(respond: F) -> String
+where
+    F: Fn(&str, &Value) -> Option<Value> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let (_, body) = read_http_request(&mut socket).await;
+            let call: Value = serde_json::from_slice(&body).unwrap();
+            let method = call["method"].as_str().unwrap();
+            let response = match respond(method, &call["params"]) {
+                Some(result) => json!({"jsonrpc": "2.0", "id": call["id"], "result": result}),
+                None => json!({"jsonrpc": "2.0", "id": call["id"], "error": {"code": -32601, "message": method}}),
+            }.to_string();
+            let reply = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                response.len(),
+                response,
+            );
+            socket.write_all(reply.as_bytes()).await.unwrap();
+        }
+    });
+    url
+}
+
+/// A local address for a server under test to bind.
+pub fn free_addr() -> SocketAddr {
+    std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap()
+}
+
+/// Sends a raw HTTP `request` to `addr` once a server listens there and
+/// returns the whole response.
+pub async fn http_request(addr: SocketAddr, request: &str) -> String {
+    let mut stream = loop {
+        match TcpStream::connect(addr).await {
+            Ok(s) => break s,
+            Err(_) => tokio::time::sleep(std::time::Duration::from_millis(20)).await,
+        }
+    };
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
 
 pub async fn send_messages(route: &Route) {
     let inbox = IVeaInboxArbToEth::new(route.inbox_address, route.inbox_provider.clone());
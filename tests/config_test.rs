@@ -0,0 +1,136 @@
+mod common;
+
+use alloy::primitives::Address;
+use std::str::FromStr;
+use vea_validator::config::{ChainInfo, ConfigFile, Finality, RouteContracts};
+use vea_validator::tasks::claim_policy::{ClaimMode, ClaimPolicy};
+use common::{example_config, validate_err};
+
+#[test]
+fn test_example_config_loads_and_validates() {
+    let file = example_config();
+    file.validate().unwrap();
+
+    assert_eq!(file.routes.len(), 2);
+    assert_eq!(file.routes[0].contracts, RouteContracts::ArbToEth);
+    assert_eq!(file.routes[1].contracts, RouteContracts::ArbToGnosis);
+    assert_eq!(file.chains[&100].native_currency, "xDAI");
+    assert_eq!(file.chains[&1].native_currency, "ETH");
+    assert_eq!(
+        file.chains[&100].deposit_token,
+        Some(Address::from_str("0x8d74e5e4DA11629537C4575cB0f33b4F0Dfa42EB").unwrap())
+    );
+}
+
+#[test]
+fn test_builtin_config_requires_addresses() {
+    let mut file = ConfigFile::builtin();
+    for chain in file.chains.values_mut() {
+        chain.rpc_urls = vec!["http://localhost:8545".into()];
+    }
+    assert_eq!(validate_err(&file), "VEA_INBOX_ARB_TO_ETH must be set");
+}
+
+#[test]
+fn test_config_rejects_unknown_chain() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, r#"{
+        "chains": {},
+        "routes": [{
+            "name": "ARB_TO_ETH",
+            "contracts": "arb_to_eth",
+            "inbox_chain_id": 421614,
+            "outbox_chain_id": 11155111
+        }],
+        "arb_outbox_chain_id": 11155111
+    }"#).unwrap();
+
+    let file = ConfigFile::load(&path).unwrap();
+    assert_eq!(validate_err(&file), "Route ARB_TO_ETH references unknown chain 421614");
+}
+
+#[test]
+fn test_gnosis_route_requires_deposit_token() {
+    let mut file = example_config();
+    file.chains.get_mut(&100).unwrap().deposit_token = None;
+    assert_eq!(validate_err(&file), "WETH_GNOSIS must be set");
+}
+
+#[test]
+fn test_retry_config_per_kind_overrides() {
+    let mut file = example_config();
+    let default = file.retry.policy("SendSnapshot");
+    assert_eq!((1..=9).map(|n| default.backoff(n)).collect::<Vec<_>>(), vec![15, 30, 60, 120, 240, 480, 960, 1800, 1800]);
+    assert!(!default.exhausted(49));
+    assert!(default.exhausted(50));
+    assert!(!file.retry.policy("Challenge").exhausted(u32::MAX));
+
+    file.retry.kinds.insert("Chalenge".to_string(), default);
+    assert_eq!(validate_err(&file), "Unknown task kind Chalenge in retry config");
+}
+
+#[test]
+fn test_dispatcher_needs_a_worker() {
+    let mut file = example_config();
+    assert_eq!(file.dispatcher.max_concurrent_tasks, 4);
+    file.dispatcher.max_concurrent_tasks = 0;
+    assert_eq!(validate_err(&file), "dispatcher.max_concurrent_tasks must be at least 1");
+}
+
+#[test]
+fn test_tx_replacement_bounds() {
+    let mut file = example_config();
+    assert_eq!(file.tx.replace_after_blocks, 5);
+    assert_eq!(file.tx.bump_percent, 20);
+    assert_eq!(file.tx.fee_cap_percent, 400);
+    file.validate().unwrap();
+
+    file.tx.bump_percent = 5;
+    assert_eq!(validate_err(&file), "tx.bump_percent must be at least 10");
+
+    file.tx.bump_percent = 20;
+    file.tx.fee_cap_percent = 50;
+    assert_eq!(validate_err(&file), "tx.fee_cap_percent must be at least 100");
+}
+
+#[test]
+fn test_chain_fee_policy() {
+    let mut file = example_config();
+    let ethereum = &file.chains[&1].fees;
+    assert_eq!(ethereum.high.fee_percent, 150);
+    assert_eq!(ethereum.low.max_fee_per_gas, Some(50_000_000_000));
+    assert_eq!(ethereum.defer_above_base_fee, Some(30_000_000_000));
+    assert_eq!(file.chains[&42161].fees.defer_above_base_fee, None);
+    file.validate().unwrap();
+
+    let high = &mut file.chains.get_mut(&1).unwrap().fees.high;
+    high.max_priority_fee_per_gas = Some(2_000_000_000_000);
+    assert_eq!(validate_err(&file), "Ethereum fees.high.max_priority_fee_per_gas exceeds max_fee_per_gas");
+}
+
+#[test]
+fn test_claim_policy() {
+    let file = example_config();
+    assert_eq!(file.claims.mode, ClaimMode::Always);
+    assert_eq!(file.claims.wait_secs, 1800);
+
+    let json = r#"{ "mode": "if_profitable", "reward_wei": 5000000000000000 }"#;
+    let policy: ClaimPolicy = serde_json::from_str(json).unwrap();
+    assert_eq!(policy.mode, ClaimMode::IfProfitable);
+    assert_eq!(policy.reward_wei, 5_000_000_000_000_000);
+    assert_eq!(policy.withdraw_gas, ClaimPolicy::default().withdraw_gas);
+}
+
+#[test]
+fn test_chain_finality() {
+    let file = example_config();
+    assert!(file.chains.values().all(|c| c.finality == Finality::Buffer));
+
+    let json = r#"{ "name": "Ethereum", "avg_block_millis": 12000, "finality": "finalized" }"#;
+    let chain: ChainInfo = serde_json::from_str(json).unwrap();
+    assert_eq!(chain.finality, Finality::Finalized);
+
+    assert_eq!(Finality::from_str("safe").unwrap(), Finality::Safe);
+    assert_eq!(Finality::from_str("latest").unwrap_err(), "Invalid finality latest (expected buffer, safe or finalized)");
+}
@@ -0,0 +1,25 @@
+use vea_validator::config::RouteSettings;
+use vea_validator::tasks::deadline::{DeadlineConfig, Urgency};
+
+#[test]
+fn test_urgency_escalates_towards_deadline() {
+    let config = DeadlineConfig { elevated_secs: 600, critical_secs: 60, elevated_fee_percent: 150, critical_fee_percent: 300 };
+    assert_eq!(config.urgency(None, 5000), Urgency::Normal);
+    assert_eq!(config.urgency(Some(1000), 0), Urgency::Normal);
+    assert_eq!(config.urgency(Some(1000), 400), Urgency::Elevated);
+    assert_eq!(config.urgency(Some(1000), 940), Urgency::Critical);
+    assert_eq!(config.urgency(Some(1000), 1000), Urgency::Missed);
+    assert_eq!(config.urgency(Some(1000), 2000), Urgency::Missed);
+
+    let bids: Vec<u64> = [Urgency::Normal, Urgency::Elevated, Urgency::Critical, Urgency::Missed]
+        .into_iter().map(|u| config.fee_percent(u)).collect();
+    assert_eq!(bids, vec![100, 150, 300, 300]);
+}
+
+#[test]
+fn test_deadlines_derive_from_route_settings() {
+    let settings = RouteSettings::test_defaults();
+    assert_eq!(settings.challenge_deadline(1000), 1000 + settings.challenge_window_secs);
+    assert_eq!(settings.verify_deadline(5000), 5000 + settings.min_challenge_period);
+    assert!(settings.challenge_window_secs < settings.start_verification_delay + settings.min_challenge_period);
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use alloy::network::Ethereum;
+use alloy::primitives::{Address, U256};
+use alloy::providers::{DynProvider, ProviderBuilder};
+use std::sync::Arc;
+use vea_validator::contracts::{Claim, IVeaOutboxArbToEth, Party};
+use vea_validator::tasks::fees::FeeBid;
+use vea_validator::tasks::nonce::{NonceManager, TxConfig};
+use vea_validator::tasks::recorder::TxRecorder;
+use vea_validator::tasks::send_tx;
+use common::{example_config, validate_err};
+
+/// Nothing listens here, so any RPC call would fail the test.
+fn unreachable_provider() -> DynProvider<Ethereum> {
+    DynProvider::new(ProviderBuilder::new().connect_http("http://127.0.0.1:1".parse().unwrap()))
+}
+
+#[tokio::test]
+async fn test_send_tx_records_instead_of_sending() {
+    let provider = unreachable_provider();
+    let recorder = Arc::new(TxRecorder::default());
+    let nonces = NonceManager::new("Ethereum", provider.clone(), Address::repeat_byte(0x11), TxConfig::default(), 12000)
+        .with_recorder(recorder.clone());
+
+    let outbox_address = Address::repeat_byte(0x22);
+    let outbox = IVeaOutboxArbToEth::new(outbox_address, provider);
+    let claim = Claim {
+        stateRoot: Default::default(),
+        claimer: Address::repeat_byte(0x33),
+        timestampClaimed: 0,
+        timestampVerification: 0,
+        blocknumberVerification: 0,
+        honest: Party::None,
+        challenger: Address::ZERO,
+    };
+    let deposit = U256::from(1_000_000_000_000_000_000u128);
+    send_tx(outbox.challenge(U256::from(7), claim).value(deposit), &nonces, FeeBid::default(), "challenge", "ARB_TO_ETH")
+        .await
+        .unwrap();
+
+    let recorded = recorder.recorded();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(recorded[0].chain, "Ethereum");
+    assert_eq!(recorded[0].route, "ARB_TO_ETH");
+    assert_eq!(recorded[0].action, "challenge");
+    assert_eq!(recorded[0].to, Some(outbox_address));
+    assert_eq!(recorded[0].value, deposit);
+    assert!(!recorded[0].input.is_empty());
+    assert!(nonces.pending().is_empty());
+}
+
+#[test]
+fn test_validator_address_requires_dry_run() {
+    let mut file = example_config();
+    assert!(!file.dry_run);
+    file.validator_address = Some(Address::repeat_byte(0x44));
+    assert_eq!(validate_err(&file), "VALIDATOR_ADDRESS is only used with DRY_RUN");
+
+    file.dry_run = true;
+    file.validate().unwrap();
+}
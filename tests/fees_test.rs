@@ -0,0 +1,56 @@
+use alloy::eips::eip1559::Eip1559Estimation;
+use alloy::primitives::FixedBytes;
+use vea_validator::tasks::fees::{FeeBid, FeePolicy, FeeTier, TierPolicy};
+use vea_validator::tasks::TaskKind;
+
+const GWEI: u128 = 1_000_000_000;
+
+fn estimate(max_fee: u128, priority_fee: u128) -> Eip1559Estimation {
+    Eip1559Estimation { max_fee_per_gas: max_fee * GWEI, max_priority_fee_per_gas: priority_fee * GWEI }
+}
+
+#[test]
+fn test_task_kinds_map_to_tiers() {
+    assert_eq!(TaskKind::Challenge.fee_tier(), FeeTier::High);
+    assert_eq!(TaskKind::SendSnapshot.fee_tier(), FeeTier::High);
+    assert_eq!(TaskKind::Claim { state_root: FixedBytes::ZERO }.fee_tier(), FeeTier::Normal);
+    assert_eq!(TaskKind::VerifySnapshot.fee_tier(), FeeTier::Normal);
+    assert_eq!(TaskKind::WithdrawDeposit.fee_tier(), FeeTier::Low);
+    assert_eq!(TaskKind::SaveSnapshot.fee_tier(), FeeTier::Low);
+}
+
+#[test]
+fn test_bid_scales_and_caps() {
+    let policy = FeePolicy {
+        high: TierPolicy { fee_percent: 200, max_fee_per_gas: Some(100 * GWEI), max_priority_fee_per_gas: Some(5 * GWEI) },
+        ..FeePolicy::default()
+    };
+
+    let bid = policy.bid(FeeTier::High, 100);
+    assert_eq!(bid.price(estimate(40, 2)), (80 * GWEI, 4 * GWEI));
+    assert_eq!(bid.price(estimate(80, 4)), (100 * GWEI, 5 * GWEI));
+
+    // A deadline raises the multiplier but the caps still hold.
+    let urgent = policy.bid(FeeTier::High, 300);
+    assert_eq!(urgent.fee_percent, 300);
+    assert_eq!(urgent.price(estimate(40, 2)), (100 * GWEI, 5 * GWEI));
+
+    assert_eq!(policy.bid(FeeTier::Normal, 100), FeeBid::default());
+    assert_eq!(FeeBid::default().price(estimate(40, 2)), (40 * GWEI, 2 * GWEI));
+}
+
+#[test]
+fn test_priority_fee_never_exceeds_max_fee() {
+    let bid = FeeBid { fee_percent: 100, max_fee_per_gas: Some(GWEI), max_priority_fee_per_gas: None };
+    assert_eq!(bid.price(estimate(10, 3)), (GWEI, GWEI));
+}
+
+#[test]
+fn test_only_low_tier_defers_above_base_fee() {
+    let policy = FeePolicy { defer_above_base_fee: Some(30 * GWEI), ..FeePolicy::default() };
+    assert!(policy.should_defer(FeeTier::Low, 31 * GWEI));
+    assert!(!policy.should_defer(FeeTier::Low, 30 * GWEI));
+    assert!(!policy.should_defer(FeeTier::Normal, 100 * GWEI));
+    assert!(!policy.should_defer(FeeTier::High, 100 * GWEI));
+    assert!(!FeePolicy::default().should_defer(FeeTier::Low, 1000 * GWEI));
+}
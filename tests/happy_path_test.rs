@@ -46,7 +46,7 @@ async fn test_start_verification() {
     let claim_store = Arc::new(Mutex::new(ClaimStore::new(&claims_path)));
     let wallet_address = c.wallet.default_signer().address();
     let indexer = EventIndexer::new(route.clone(), wallet_address, task_store.clone(), claim_store.clone());
-    indexer.initialize().await;
+    indexer.initialize().await.unwrap();
     task_store.lock().unwrap().set_on_sync(true);
     let dispatcher = TaskDispatcher::new(c.clone(), route.clone(), task_store, claim_store);
 
@@ -97,7 +97,7 @@ async fn test_verify_snapshot() {
     let claim_store = Arc::new(Mutex::new(ClaimStore::new(&claims_path)));
     let wallet_address = c.wallet.default_signer().address();
     let indexer = EventIndexer::new(route.clone(), wallet_address, task_store.clone(), claim_store.clone());
-    indexer.initialize().await;
+    indexer.initialize().await.unwrap();
     task_store.lock().unwrap().set_on_sync(true);
     let dispatcher = TaskDispatcher::new(c.clone(), route.clone(), task_store, claim_store);
 
@@ -158,7 +158,7 @@ async fn test_full_happy_path_via_indexer() {
     let claim_store = Arc::new(Mutex::new(ClaimStore::new(&claims_path)));
     let wallet_address = c.wallet.default_signer().address();
     let indexer = EventIndexer::new(route.clone(), wallet_address, task_store.clone(), claim_store.clone());
-    indexer.initialize().await;
+    indexer.initialize().await.unwrap();
     task_store.lock().unwrap().set_on_sync(true);
     let dispatcher = TaskDispatcher::new(c.clone(), route.clone(), task_store, claim_store.clone());
 
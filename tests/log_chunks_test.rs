@@ -0,0 +1,72 @@
+mod common;
+
+use std::time::Duration;
+use vea_validator::log_chunks::{LogChunkConfig, LogChunker, RangeError};
+use common::{example_config, validate_err};
+
+const FAST: Duration = Duration::from_millis(100);
+
+#[test]
+fn test_range_errors_are_classified() {
+    let cases = [
+        ("query returned more than 10000 results", Some(RangeError::TooManyResults)),
+        ("Log response size exceeded. You can make eth_getLogs requests with up to a 2K block range", Some(RangeError::TooManyResults)),
+        ("eth_getLogs is limited to a 10,000 range", Some(RangeError::RangeTooLarge)),
+        ("exceed maximum block range: 5000", Some(RangeError::RangeTooLarge)),
+        ("connection reset by peer", None),
+    ];
+    for (message, expected) in cases {
+        assert_eq!(RangeError::classify(message), expected, "{}", message);
+    }
+}
+
+#[test]
+fn test_chunk_grows_on_small_fast_queries() {
+    let chunks = LogChunker::new("Test", LogChunkConfig { initial_blocks: 1000, max_blocks: 3000, ..Default::default() });
+    chunks.record_success(1000, 999, Duration::from_secs(5));
+    assert_eq!(chunks.size(), 1000, "slow queries do not grow");
+    chunks.record_success(1000, 1000, FAST);
+    assert_eq!(chunks.size(), 1000, "busy ranges do not grow");
+    chunks.record_success(400, 0, FAST);
+    assert_eq!(chunks.size(), 1000, "short tail chunks do not grow");
+
+    chunks.record_success(1000, 0, FAST);
+    assert_eq!(chunks.size(), 2000);
+    chunks.record_success(2000, 0, FAST);
+    assert_eq!(chunks.size(), 3000, "capped at max_blocks");
+}
+
+#[test]
+fn test_chunk_shrinks_on_refused_ranges() {
+    let chunks = LogChunker::new("Test", LogChunkConfig { initial_blocks: 16_000, min_blocks: 1000, ..Default::default() });
+    assert!(!chunks.record_error("connection reset by peer"));
+    assert_eq!(chunks.size(), 16_000);
+
+    assert!(chunks.record_error("eth_getLogs is limited to a 10,000 range"));
+    assert_eq!(chunks.size(), 9999);
+    chunks.record_success(9999, 0, FAST);
+    assert_eq!(chunks.size(), 9999, "never grows past a refused range");
+
+    assert!(chunks.record_error("query returned more than 10000 results"));
+    assert_eq!(chunks.size(), 4999);
+    chunks.record_success(4999, 0, FAST);
+    assert_eq!(chunks.size(), 9998, "grows back after too many results");
+
+    assert!(chunks.record_error("block range too large"));
+    assert_eq!(chunks.size(), 4999, "halved without a stated limit");
+    for _ in 0..4 {
+        chunks.record_error("query returned more than 10000 results");
+    }
+    assert_eq!(chunks.size(), 1000);
+    assert!(!chunks.record_error("query returned more than 10000 results"), "cannot shrink below min_blocks");
+}
+
+#[test]
+fn test_log_chunk_bounds_are_validated() {
+    let mut file = example_config();
+    assert_eq!(file.chains[&42161].log_chunks.initial_blocks, 2000);
+    file.validate().unwrap();
+
+    file.chains.get_mut(&1).unwrap().log_chunks.min_blocks = 0;
+    assert_eq!(validate_err(&file), "Ethereum log_chunks needs 1 <= min_blocks <= max_blocks");
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use vea_validator::metrics::{self, METRICS};
+use common::{free_addr, http_request};
+
+#[test]
+fn test_render_exposition_format() {
+    metrics::set_indexer_block("TEST_RENDER", "Inbox", 1234);
+    metrics::record_task("TEST_RENDER", "Challenge", "success");
+    metrics::record_task("TEST_RENDER", "Challenge", "success");
+    metrics::record_tx("TEST_RENDER", "challenge", 21000, 2_000_000_000, true);
+
+    let out = METRICS.render();
+    assert!(out.contains("# TYPE vea_indexer_last_block gauge\n"));
+    assert!(out.contains("vea_indexer_last_block{route=\"TEST_RENDER\",chain=\"Inbox\"} 1234\n"));
+    assert!(out.contains("# TYPE vea_task_executions_total counter\n"));
+    assert!(out.contains("vea_task_executions_total{route=\"TEST_RENDER\",kind=\"Challenge\",result=\"success\"} 2\n"));
+    assert_eq!(
+        METRICS.get("vea_gas_spent_wei_total", &[("route", "TEST_RENDER"), ("action", "challenge")]),
+        Some(42_000_000_000_000.0)
+    );
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint_serves_registry() {
+    let addr = free_addr();
+    tokio::spawn(metrics::serve(addr));
+    metrics::set_on_sync("TEST_HTTP", true);
+
+    let response = http_request(addr, "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").await;
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains("vea_on_sync{route=\"TEST_HTTP\"} 1\n"));
+}
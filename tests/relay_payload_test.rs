@@ -0,0 +1,129 @@
+use alloy::primitives::{Address, FixedBytes, U256};
+use alloy::sol_types::SolCall;
+use vea_validator::adapters::{ExpectedRelay, RelayPayload};
+use vea_validator::alerts::AlertKind;
+use vea_validator::config::RouteContracts;
+use vea_validator::contracts::{Claim, IRouterArbToGnosis, IVeaOutboxOnL1, Party};
+
+fn claim() -> Claim {
+    Claim {
+        stateRoot: FixedBytes::repeat_byte(0xaa),
+        claimer: Address::repeat_byte(0x11),
+        timestampClaimed: 1_700_000_000,
+        timestampVerification: 0,
+        blocknumberVerification: 0,
+        honest: Party::None,
+        challenger: Address::repeat_byte(0x22),
+    }
+}
+
+#[test]
+fn test_decodes_arb_to_eth_relay() {
+    let data = IVeaOutboxOnL1::resolveDisputedClaimCall {
+        _epoch: U256::from(42),
+        _stateRoot: FixedBytes::repeat_byte(0xbb),
+        _claim: claim(),
+    }.abi_encode();
+
+    let payload = RouteContracts::ArbToEth.adapter().decode_relay(&data).unwrap();
+    assert_eq!(payload, RelayPayload { epoch: 42, state_root: FixedBytes::repeat_byte(0xbb), claim: claim() });
+    assert!(RouteContracts::ArbToGnosis.adapter().decode_relay(&data).is_none());
+}
+
+#[test]
+fn test_decodes_arb_to_gnosis_relay() {
+    let data = IRouterArbToGnosis::routeCall {
+        _epoch: U256::from(42),
+        _stateroot: FixedBytes::repeat_byte(0xbb),
+        _gasLimit: U256::from(500_000),
+        _claim: claim(),
+    }.abi_encode();
+
+    let payload = RouteContracts::ArbToGnosis.adapter().decode_relay(&data).unwrap();
+    assert_eq!(payload, RelayPayload { epoch: 42, state_root: FixedBytes::repeat_byte(0xbb), claim: claim() });
+    assert!(RouteContracts::ArbToEth.adapter().decode_relay(&data).is_none());
+}
+
+#[test]
+fn test_rejects_truncated_or_oversized_epochs() {
+    let mut data = IVeaOutboxOnL1::resolveDisputedClaimCall {
+        _epoch: U256::MAX,
+        _stateRoot: FixedBytes::ZERO,
+        _claim: claim(),
+    }.abi_encode();
+    assert!(RouteContracts::ArbToEth.adapter().decode_relay(&data).is_none());
+
+    data.truncate(40);
+    assert!(RouteContracts::ArbToEth.adapter().decode_relay(&data).is_none());
+}
+
+fn relay_data() -> Vec<u8> {
+    IVeaOutboxOnL1::resolveDisputedClaimCall {
+        _epoch: U256::from(42),
+        _stateRoot: FixedBytes::repeat_byte(0xbb),
+        _claim: claim(),
+    }.abi_encode()
+}
+
+fn expected() -> ExpectedRelay {
+    ExpectedRelay {
+        destination: Address::repeat_byte(0x33),
+        epoch: 42,
+        state_root: FixedBytes::repeat_byte(0xbb),
+        claim: Some(claim()),
+    }
+}
+
+#[test]
+fn test_accepts_matching_relay() {
+    let adapter = RouteContracts::ArbToEth.adapter();
+    assert!(expected().check(adapter.as_ref(), Address::repeat_byte(0x33), &relay_data()).is_ok());
+
+    let unindexed = ExpectedRelay { claim: None, ..expected() };
+    assert!(unindexed.check(adapter.as_ref(), Address::repeat_byte(0x33), &relay_data()).is_ok());
+}
+
+#[test]
+fn test_rejects_wrong_destination() {
+    let adapter = RouteContracts::ArbToEth.adapter();
+    let err = expected().check(adapter.as_ref(), Address::repeat_byte(0x44), &relay_data()).unwrap_err();
+    assert!(err.starts_with("destination"), "{}", err);
+}
+
+#[test]
+fn test_rejects_undecodable_data() {
+    let adapter = RouteContracts::ArbToGnosis.adapter();
+    let err = expected().check(adapter.as_ref(), Address::repeat_byte(0x33), &relay_data()).unwrap_err();
+    assert_eq!(err, "data is not a snapshot relay");
+}
+
+#[test]
+fn test_rejects_wrong_epoch() {
+    let adapter = RouteContracts::ArbToEth.adapter();
+    let other = ExpectedRelay { epoch: 43, ..expected() };
+    let err = other.check(adapter.as_ref(), Address::repeat_byte(0x33), &relay_data()).unwrap_err();
+    assert_eq!(err, "data resolves epoch 42");
+}
+
+#[test]
+fn test_rejects_wrong_state_root() {
+    let adapter = RouteContracts::ArbToEth.adapter();
+    let other = ExpectedRelay { state_root: FixedBytes::repeat_byte(0xcc), ..expected() };
+    let err = other.check(adapter.as_ref(), Address::repeat_byte(0x33), &relay_data()).unwrap_err();
+    assert!(err.starts_with("state root"), "{}", err);
+}
+
+#[test]
+fn test_rejects_claim_other_than_indexed() {
+    let adapter = RouteContracts::ArbToEth.adapter();
+    let indexed = Claim { challenger: Address::ZERO, ..claim() };
+    let other = ExpectedRelay { claim: Some(indexed), ..expected() };
+    let err = other.check(adapter.as_ref(), Address::repeat_byte(0x33), &relay_data()).unwrap_err();
+    assert!(err.starts_with("claim"), "{}", err);
+}
+
+#[test]
+fn test_relay_mismatch_pages() {
+    assert_eq!(AlertKind::RelayMismatch.name(), "relay_mismatch");
+    assert_eq!(AlertKind::RelayMismatch.severity(), "critical");
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use alloy::consensus::Transaction;
+use alloy::eips::eip2718::Encodable2718;
+use alloy::network::{Ethereum, EthereumWallet, NetworkWallet, TransactionBuilder, TxSignerSync};
+use alloy::primitives::{Address, U256};
+use alloy::rpc::types::TransactionRequest;
+use alloy::signers::local::PrivateKeySigner;
+use serde_json::{json, Value};
+use vea_validator::signer::{RemoteSigner, SignerConfig};
+use common::{example_config, mock_json_rpc, validate_err};
+
+#[derive(Clone, Copy)]
+enum Mock {
+    /// Returns the raw transaction, as Web3Signer does.
+    Raw,
+    /// Returns `{raw, tx}`, as Clef and geth do.
+    Wrapped,
+    /// Signs a transaction with a different nonce.
+    Tampered,
+}
+
+fn request() -> TransactionRequest {
+    TransactionRequest::default()
+        .to(Address::repeat_byte(0x22))
+        .value(U256::from(1_000u64))
+        .nonce(7)
+        .gas_limit(100_000)
+        .max_fee_per_gas(30_000_000_000)
+        .max_priority_fee_per_gas(1_000_000_000)
+        .with_chain_id(1)
+}
+
+fn respond(signer: &PrivateKeySigner, mock: Mock, method: &str, params: &Value) -> Option<Value> {
+    Some(match method {
+        "eth_accounts" => json!([signer.address()]),
+        "eth_signTransaction" => {
+            let mut request: TransactionRequest = serde_json::from_value(params[0].clone()).unwrap();
+            if let Mock::Tampered = mock {
+                request.nonce = Some(request.nonce.unwrap() + 1);
+            }
+            let mut tx = request.build_typed_tx().unwrap();
+            let signature = signer.sign_transaction_sync(&mut tx).unwrap();
+            let raw = format!("0x{}", hex::encode(tx.into_envelope(signature).encoded_2718()));
+            match mock {
+                Mock::Wrapped => json!({"raw": raw, "tx": {}}),
+                Mock::Raw | Mock::Tampered => json!(raw),
+            }
+        }
+        _ => return None,
+    })
+}
+
+/// A JSON-RPC signer holding `signer`'s key.
+async fn mock_signer(signer: PrivateKeySigner, mock: Mock) -> String {
+    mock_json_rpc(move |method, params| respond(&signer, mock, method, params)).await
+}
+
+#[tokio::test]
+async fn test_remote_signer_signs_through_json_rpc() {
+    for mock in [Mock::Raw, Mock::Wrapped] {
+        let key = PrivateKeySigner::random();
+        let address = key.address();
+        let url = mock_signer(key, mock).await;
+
+        let remote = RemoteSigner::new(&url, address);
+        assert_eq!(remote.accounts().await.unwrap(), vec![address]);
+
+        let wallet = SignerConfig::Remote { url, address }.load().unwrap().unwrap();
+        assert_eq!(NetworkWallet::<Ethereum>::default_signer_address(&wallet), address);
+        let signed = NetworkWallet::<Ethereum>::sign_request(&wallet, request()).await.unwrap();
+        assert_eq!(signed.signature().recover_address_from_prehash(&signed.signature_hash()).unwrap(), address);
+        assert_eq!(signed.nonce(), 7);
+    }
+}
+
+#[tokio::test]
+async fn test_remote_signer_rejects_foreign_signatures() {
+    let key = PrivateKeySigner::random();
+    let url = mock_signer(key.clone(), Mock::Tampered).await;
+    let wallet = SignerConfig::Remote { url, address: key.address() }.load().unwrap().unwrap();
+    let err = NetworkWallet::<Ethereum>::sign_request(&wallet, request()).await.unwrap_err();
+    assert!(err.to_string().contains("remote signer signed a different transaction"), "{}", err);
+
+    let url = mock_signer(PrivateKeySigner::random(), Mock::Raw).await;
+    let wallet = SignerConfig::Remote { url, address: key.address() }.load().unwrap().unwrap();
+    let err = NetworkWallet::<Ethereum>::sign_request(&wallet, request()).await.unwrap_err();
+    assert!(err.to_string().contains(&format!("remote signer did not sign as {}", key.address())), "{}", err);
+}
+
+#[test]
+fn test_keystore_is_decrypted_with_password_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let key = PrivateKeySigner::random();
+    PrivateKeySigner::encrypt_keystore(dir.path(), &mut rand::thread_rng(), key.to_bytes(), "hunter2", Some("validator.json")).unwrap();
+    let password_file = dir.path().join("password");
+    std::fs::write(&password_file, "hunter2\n").unwrap();
+
+    let config = SignerConfig::Keystore { path: dir.path().join("validator.json"), password_file: password_file.clone() };
+    let wallet: EthereumWallet = config.load().unwrap().unwrap();
+    assert_eq!(wallet.default_signer().address(), key.address());
+
+    std::fs::write(&password_file, "hunter3\n").unwrap();
+    let err = config.load().unwrap_err();
+    assert!(err.to_string().starts_with("Cannot decrypt keystore"), "{}", err);
+}
+
+#[test]
+fn test_remote_signer_url_is_validated() {
+    let mut file = example_config();
+    assert!(matches!(file.signer, SignerConfig::Key));
+
+    file.signer = SignerConfig::Remote { url: "localhost:9000".to_string(), address: Address::repeat_byte(0x11) };
+    assert_eq!(validate_err(&file), "Invalid remote signer URL: localhost:9000");
+
+    file.signer = SignerConfig::Remote { url: "http://localhost:9000".to_string(), address: Address::repeat_byte(0x11) };
+    file.validate().unwrap();
+}
@@ -0,0 +1,289 @@
+use alloy::primitives::{Address, FixedBytes};
+use std::sync::{Arc, Mutex};
+use vea_validator::tasks::storage::{commit, open_route_stores, StorageKind, StoreOp, MAX_CHECKPOINTS};
+use vea_validator::tasks::{ClaimData, ClaimStore, IndexedChain, Task, TaskKind, TaskStore, Undo};
+
+fn claim(epoch: u64) -> ClaimData {
+    ClaimData {
+        epoch,
+        state_root: FixedBytes::<32>::from([0xAB; 32]),
+        claimer: Address::repeat_byte(0x11),
+        timestamp_claimed: 1000,
+        timestamp_verification: 0,
+        blocknumber_verification: 0,
+        honest: "None".to_string(),
+        challenger: Address::ZERO,
+    }
+}
+
+fn exercise_stores(kind: StorageKind) {
+    let dir = tempfile::tempdir().unwrap();
+    let (task_store, claim_store) = open_route_stores(kind, dir.path(), "ARB_TO_ETH");
+
+    task_store.initialize_sync(100, 10, 20);
+    task_store.add_task(Task::new(1, 500, TaskKind::SaveSnapshot));
+    task_store.add_task(Task::new(2, 600, TaskKind::Challenge));
+    task_store.reschedule_task(&Task::new(2, 0, TaskKind::Challenge), 900);
+    task_store.remove_task(&Task::new(1, 0, TaskKind::SaveSnapshot));
+    task_store.set_on_sync(true);
+
+    let state = task_store.load();
+    assert_eq!(state.indexing_since, Some(100));
+    assert_eq!(state.inbox_last_block, Some(10));
+    assert_eq!(state.outbox_last_block, Some(20));
+    assert!(state.on_sync);
+    assert_eq!(state.tasks.len(), 1);
+    assert_eq!(state.tasks[0].epoch, 2);
+    assert_eq!(state.tasks[0].execute_after, 900);
+
+    claim_store.store(claim(7));
+    claim_store.update(7, |c| c.challenger = Address::repeat_byte(0x22));
+    assert!(claim_store.exists(7));
+    assert_eq!(claim_store.get(7).challenger, Address::repeat_byte(0x22));
+    assert!(claim_store.has_state_root_in_recent_claims(FixedBytes::<32>::from([0xAB; 32]), 1000));
+    assert!(!claim_store.has_state_root_in_recent_claims(FixedBytes::<32>::from([0xAB; 32]), 1001));
+
+    let task_store = Arc::new(Mutex::new(task_store));
+    let claim_store = Arc::new(Mutex::new(claim_store));
+    commit(&claim_store, &task_store, vec![
+        StoreOp::StoreClaim(claim(8)),
+        StoreOp::AddTask(Task::new(8, 1000, TaskKind::ValidateClaim)),
+    ]);
+    assert!(claim_store.lock().unwrap().exists(8));
+    assert!(task_store.lock().unwrap().load().tasks.iter().any(|t| t.epoch == 8));
+
+    claim_store.lock().unwrap().remove(7);
+    assert!(!claim_store.lock().unwrap().exists(7));
+
+    drop(task_store);
+    drop(claim_store);
+    let (task_store, claim_store) = open_route_stores(kind, dir.path(), "ARB_TO_ETH");
+    assert_eq!(task_store.load().tasks.len(), 2);
+    assert!(claim_store.exists(8));
+}
+
+#[test]
+fn test_json_backend() {
+    exercise_stores(StorageKind::Json);
+}
+
+#[test]
+fn test_sqlite_backend() {
+    exercise_stores(StorageKind::Sqlite);
+}
+
+fn exercise_dead_letters(kind: StorageKind) {
+    let dir = tempfile::tempdir().unwrap();
+    let (task_store, _) = open_route_stores(kind, dir.path(), "ARB_TO_ETH");
+    let task = Task::new(4, 100, TaskKind::SendSnapshot);
+    task_store.add_task(task.clone());
+
+    task_store.record_failure(&task, "RPC unavailable: timeout", 130);
+    task_store.record_failure(&task, "reverted: nope", 160);
+    let failing = task_store.find_task(4, "SendSnapshot").unwrap();
+    assert_eq!(failing.attempts, 2);
+    assert_eq!(failing.last_error.as_deref(), Some("reverted: nope"));
+    assert_eq!(failing.due_at(), 160);
+
+    task_store.dead_letter(&failing, "reverted: still nope");
+    let state = task_store.load();
+    assert!(state.tasks.is_empty());
+    assert_eq!(state.dead_letters.len(), 1);
+    assert_eq!(state.dead_letters[0].attempts, 3);
+    assert_eq!(state.dead_letters[0].last_error.as_deref(), Some("reverted: still nope"));
+
+    drop(task_store);
+    let (task_store, _) = open_route_stores(kind, dir.path(), "ARB_TO_ETH");
+    let dead = task_store.find_dead_letter(4, "SendSnapshot").expect("dead letter persisted");
+    task_store.requeue_dead_letter(&dead, 500);
+    let state = task_store.load();
+    assert!(state.dead_letters.is_empty());
+    assert_eq!(state.tasks[0].attempts, 0);
+    assert_eq!(state.tasks[0].last_error, None);
+    assert_eq!(state.tasks[0].due_at(), 500);
+
+    task_store.dead_letter(&state.tasks[0], "fatal: gone");
+    task_store.remove_dead_letter(&state.tasks[0]);
+    let state = task_store.load();
+    assert!(state.tasks.is_empty() && state.dead_letters.is_empty());
+}
+
+fn exercise_missed_deadlines(kind: StorageKind) {
+    let dir = tempfile::tempdir().unwrap();
+    let (task_store, _) = open_route_stores(kind, dir.path(), "ARB_TO_ETH");
+    let task = Task::new(9, 100, TaskKind::Challenge).with_deadline(1000);
+    task_store.add_task(task.clone());
+    task_store.record_missed_deadline(&task, 1001);
+    task_store.record_missed_deadline(&task, 1016);
+    task_store.record_missed_deadline(&Task::new(9, 100, TaskKind::ValidateClaim), 1016);
+
+    drop(task_store);
+    let (task_store, _) = open_route_stores(kind, dir.path(), "ARB_TO_ETH");
+    let state = task_store.load();
+    assert_eq!(state.tasks[0].deadline, Some(1000));
+    assert_eq!(state.missed_deadlines.len(), 1, "recorded once, tasks without a deadline ignored");
+    assert_eq!((state.missed_deadlines[0].deadline, state.missed_deadlines[0].detected_at), (1000, 1001));
+}
+
+fn exercise_checkpoints(kind: StorageKind) {
+    let dir = tempfile::tempdir().unwrap();
+    let (task_store, _) = open_route_stores(kind, dir.path(), "ARB_TO_ETH");
+    let hash = |block: u64| FixedBytes::<32>::left_padding_from(&block.to_be_bytes());
+    task_store.initialize_sync(100, 10, 20);
+
+    task_store.backend().apply(&[
+        StoreOp::AddTask(Task::new(5, 500, TaskKind::ValidateClaim)),
+        StoreOp::RecordUndo(IndexedChain::Outbox, vec![Undo::RemoveTask { epoch: 5, kind: "ValidateClaim".to_string() }]),
+        StoreOp::RecordUndo(IndexedChain::Inbox, vec![Undo::RemoveClaim { epoch: 6 }]),
+    ]);
+    task_store.advance_cursor(IndexedChain::Outbox, 30, hash(30));
+
+    drop(task_store);
+    let (task_store, _) = open_route_stores(kind, dir.path(), "ARB_TO_ETH");
+    let state = task_store.load();
+    assert_eq!(state.outbox_last_block, Some(30));
+    assert_eq!(state.checkpoints.len(), 1);
+    let checkpoint = &state.checkpoints[0];
+    assert_eq!((checkpoint.chain, checkpoint.block, checkpoint.hash), (IndexedChain::Outbox, 30, hash(30)));
+    assert!(matches!(checkpoint.undo.as_slice(), [Undo::RemoveTask { epoch: 5, .. }]));
+    assert_eq!(state.pending_undo.len(), 1, "the inbox entry waits for an inbox checkpoint");
+    assert_eq!(state.pending_undo[0].0, IndexedChain::Inbox);
+
+    for block in 31..31 + MAX_CHECKPOINTS as u64 {
+        task_store.advance_cursor(IndexedChain::Outbox, block, hash(block));
+    }
+    task_store.advance_cursor(IndexedChain::Inbox, 11, hash(11));
+    let state = task_store.load();
+    let outbox: Vec<u64> = state.checkpoints.iter().filter(|c| c.chain == IndexedChain::Outbox).map(|c| c.block).collect();
+    assert_eq!(outbox.len(), MAX_CHECKPOINTS);
+    assert_eq!(outbox[0], 31, "oldest checkpoints pruned first");
+    assert!(state.pending_undo.is_empty());
+
+    task_store.backend().apply(&[
+        StoreOp::RecordUndo(IndexedChain::Outbox, vec![Undo::RemoveClaim { epoch: 7 }]),
+        StoreOp::RollBack { chain: IndexedChain::Outbox, block: 100 },
+        StoreOp::SetOutboxBlock(100),
+    ]);
+    let state = task_store.load();
+    assert_eq!(state.outbox_last_block, Some(100));
+    assert_eq!(state.checkpoints.iter().filter(|c| c.chain == IndexedChain::Outbox).map(|c| c.block).max(), Some(100));
+    assert_eq!(state.checkpoints.iter().filter(|c| c.chain == IndexedChain::Inbox).count(), 1);
+    assert!(state.pending_undo.is_empty());
+
+    task_store.initialize_sync(200, 50, 60);
+    assert!(task_store.load().checkpoints.is_empty());
+}
+
+#[test]
+fn test_json_checkpoints() {
+    exercise_checkpoints(StorageKind::Json);
+}
+
+#[test]
+fn test_sqlite_checkpoints() {
+    exercise_checkpoints(StorageKind::Sqlite);
+}
+
+#[test]
+fn test_json_missed_deadlines() {
+    exercise_missed_deadlines(StorageKind::Json);
+}
+
+#[test]
+fn test_sqlite_missed_deadlines() {
+    exercise_missed_deadlines(StorageKind::Sqlite);
+}
+
+#[test]
+fn test_json_dead_letters() {
+    exercise_dead_letters(StorageKind::Json);
+}
+
+#[test]
+fn test_sqlite_dead_letters() {
+    exercise_dead_letters(StorageKind::Sqlite);
+}
+
+#[test]
+fn test_sqlite_commit_is_atomic() {
+    let dir = tempfile::tempdir().unwrap();
+    let (task_store, claim_store) = open_route_stores(StorageKind::Sqlite, dir.path(), "ARB_TO_ETH");
+    claim_store.store(claim(5));
+    let task_store = Arc::new(Mutex::new(task_store));
+    let claim_store = Arc::new(Mutex::new(claim_store));
+
+    let result = std::panic::catch_unwind(|| {
+        let (task_store, claim_store) = open_route_stores(StorageKind::Sqlite, dir.path(), "ARB_TO_ETH");
+        commit(&Arc::new(Mutex::new(claim_store)), &Arc::new(Mutex::new(task_store)), vec![
+            StoreOp::AddTask(Task::new(5, 0, TaskKind::ValidateClaim)),
+            StoreOp::StoreClaim(claim(5)),
+        ]);
+    });
+    assert!(result.is_err(), "duplicate claim should abort the batch");
+    assert!(task_store.lock().unwrap().load().tasks.is_empty(), "task from aborted batch was persisted");
+    assert!(claim_store.lock().unwrap().exists(5));
+}
+
+#[test]
+fn test_legacy_json_stores_still_work_separately() {
+    let dir = tempfile::tempdir().unwrap();
+    let task_store = Arc::new(Mutex::new(TaskStore::new(dir.path().join("schedule.json"))));
+    let claim_store = Arc::new(Mutex::new(ClaimStore::new(dir.path().join("claims.json"))));
+    commit(&claim_store, &task_store, vec![
+        StoreOp::StoreClaim(claim(3)),
+        StoreOp::AddTask(Task::new(3, 0, TaskKind::ValidateClaim)),
+    ]);
+    assert!(claim_store.lock().unwrap().exists(3));
+    assert_eq!(task_store.lock().unwrap().load().tasks.len(), 1);
+}
+
+#[test]
+fn test_json_recovers_from_backup_after_truncated_write() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("arb-to-eth.json");
+    let task_store = TaskStore::new(&path);
+    task_store.initialize_sync(100, 10, 20);
+    task_store.add_task(Task::new(1, 500, TaskKind::SaveSnapshot));
+    task_store.add_task(Task::new(2, 600, TaskKind::Challenge));
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, &contents[..contents.len() / 2]).unwrap();
+
+    let state = task_store.load();
+    assert_eq!(state.inbox_last_block, Some(10));
+    assert_eq!(state.tasks.len(), 1, "should recover the previous version from .bak.1");
+
+    let restored = std::fs::read_to_string(&path).unwrap();
+    serde_json::from_str::<serde_json::Value>(&restored).expect("main file restored from backup");
+}
+
+#[test]
+fn test_json_keeps_rotating_backups() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("claims.json");
+    let claim_store = ClaimStore::new(&path);
+    for epoch in 1..=6 {
+        claim_store.store(claim(epoch));
+    }
+
+    let backup = |n: usize| dir.path().join(format!("claims.json.bak.{}", n));
+    for n in 1..=3 {
+        assert!(backup(n).exists(), "missing backup {}", n);
+    }
+    assert!(!backup(4).exists());
+
+    let newest: Vec<ClaimData> = serde_json::from_str(&std::fs::read_to_string(backup(1)).unwrap()).unwrap();
+    let oldest: Vec<ClaimData> = serde_json::from_str(&std::fs::read_to_string(backup(3)).unwrap()).unwrap();
+    assert_eq!(newest.len(), 5);
+    assert_eq!(oldest.len(), 3);
+    assert!(!dir.path().join("claims.json.tmp").exists());
+}
+
+#[test]
+#[should_panic(expected = "no valid backup found")]
+fn test_json_without_valid_backup_panics() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("schedule.json");
+    std::fs::write(&path, "{\"tasks\": [").unwrap();
+    TaskStore::new(&path).load();
+}
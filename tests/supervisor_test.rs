@@ -0,0 +1,108 @@
+use alloy::primitives::{Address, FixedBytes};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+use vea_validator::supervisor::{supervise, SupervisorConfig};
+use vea_validator::sync::LockExt;
+use vea_validator::tasks::storage::{commit, open_route_stores, StorageKind, StoreOp};
+use vea_validator::tasks::{ClaimData, Task, TaskKind};
+
+fn no_backoff(failure_budget: u32) -> SupervisorConfig {
+    SupervisorConfig { initial_backoff_secs: 0, max_backoff_secs: 0, failure_budget, budget_window_secs: 3600 }
+}
+
+#[test]
+fn test_backoff_grows_exponentially_up_to_cap() {
+    let config = SupervisorConfig { initial_backoff_secs: 5, max_backoff_secs: 60, failure_budget: 10, budget_window_secs: 3600 };
+    let secs: Vec<u64> = (1..=6).map(|n| config.backoff(n).as_secs()).collect();
+    assert_eq!(secs, vec![5, 10, 20, 40, 60, 60]);
+    assert_eq!(config.backoff(1000).as_secs(), 60);
+}
+
+#[tokio::test]
+async fn test_restarts_panicking_component_until_shutdown() {
+    let starts = Arc::new(AtomicU32::new(0));
+    let shutdown = CancellationToken::new();
+
+    let counter = starts.clone();
+    let token = shutdown.clone();
+    let handle = tokio::spawn(supervise("flaky", no_backoff(5), shutdown.clone(), move || {
+        let counter = counter.clone();
+        let token = token.clone();
+        async move {
+            if counter.fetch_add(1, Ordering::SeqCst) < 3 {
+                panic!("transient RPC outage");
+            }
+            token.cancelled().await;
+        }
+    }));
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(starts.load(Ordering::SeqCst), 4);
+    shutdown.cancel();
+    assert_eq!(handle.await.unwrap(), Ok(()));
+}
+
+fn claim(epoch: u64) -> ClaimData {
+    ClaimData {
+        epoch,
+        state_root: FixedBytes::<32>::from([0xAB; 32]),
+        claimer: Address::repeat_byte(0x11),
+        timestamp_claimed: 1000,
+        timestamp_verification: 0,
+        blocknumber_verification: 0,
+        honest: "None".to_string(),
+        challenger: Address::ZERO,
+    }
+}
+
+#[tokio::test]
+async fn test_restarts_component_that_panicked_holding_store_locks() {
+    let dir = tempfile::tempdir().unwrap();
+    let (task_store, claim_store) = open_route_stores(StorageKind::Sqlite, dir.path(), "ARB_TO_ETH");
+    claim_store.store(claim(7));
+    let task_store = Arc::new(Mutex::new(task_store));
+    let claim_store = Arc::new(Mutex::new(claim_store));
+
+    let starts = Arc::new(AtomicU32::new(0));
+    let shutdown = CancellationToken::new();
+    let (counter, token) = (starts.clone(), shutdown.clone());
+    let (tasks, claims) = (task_store.clone(), claim_store.clone());
+    let handle = tokio::spawn(supervise("indexer", no_backoff(5), shutdown.clone(), move || {
+        let (counter, token) = (counter.clone(), token.clone());
+        let (tasks, claims) = (tasks.clone(), claims.clone());
+        async move {
+            let epoch = if counter.fetch_add(1, Ordering::SeqCst) == 0 { 7 } else { 8 };
+            // The first run panics with "Duplicate claim" inside `commit`,
+            // poisoning both store locks.
+            commit(&claims, &tasks, vec![
+                StoreOp::StoreClaim(claim(epoch)),
+                StoreOp::AddTask(Task::new(epoch, 1000, TaskKind::ValidateClaim)),
+            ]);
+            token.cancelled().await;
+        }
+    }));
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(starts.load(Ordering::SeqCst), 2);
+    assert!(claim_store.is_poisoned());
+    let tasks: Vec<u64> = task_store.locked().load().tasks.iter().map(|t| t.epoch).collect();
+    assert_eq!(tasks, vec![8], "the panicked batch left nothing behind");
+    assert!(claim_store.locked().exists(8));
+    shutdown.cancel();
+    assert_eq!(handle.await.unwrap(), Ok(()));
+}
+
+#[tokio::test]
+async fn test_exhausted_failure_budget_escalates() {
+    let starts = Arc::new(AtomicU32::new(0));
+    let counter = starts.clone();
+    let result = supervise("broken", no_backoff(2), CancellationToken::new(), move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async {}
+    }).await;
+
+    assert!(result.unwrap_err().contains("broken failed 3 times"));
+    assert_eq!(starts.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,54 @@
+use alloy::contract::Error as ContractError;
+use alloy::transports::{TransportError, TransportErrorKind};
+use vea_validator::tasks::TaskError;
+
+fn error_resp(message: &str, data: Option<&str>) -> TransportError {
+    let payload = serde_json::json!({"code": 3, "message": message, "data": data});
+    TransportError::ErrorResp(serde_json::from_str(&payload.to_string()).unwrap())
+}
+
+#[test]
+fn test_revert_response_decodes_reason() {
+    // Error(string) "Epoch already claimed."
+    let data = "0x08c379a0\
+        0000000000000000000000000000000000000000000000000000000000000020\
+        0000000000000000000000000000000000000000000000000000000000000016\
+        45706f636820616c726561647920636c61696d65642e00000000000000000000";
+    match TaskError::from(error_resp("execution reverted", Some(data))) {
+        TaskError::Reverted { reason } => assert_eq!(reason, "Epoch already claimed."),
+        other => panic!("expected Reverted, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_insufficient_funds_response_is_classified() {
+    let err = error_resp("insufficient funds for gas * price + value", None);
+    assert!(matches!(TaskError::from(err), TaskError::InsufficientFunds(_)));
+}
+
+#[test]
+fn test_transport_failure_is_rpc_unavailable() {
+    let err = TaskError::from(TransportErrorKind::backend_gone());
+    assert!(matches!(err, TaskError::RpcUnavailable(_)));
+    assert_eq!(err.name(), "rpc_unavailable");
+}
+
+#[test]
+fn test_undecodable_call_results_are_rpc_unavailable() {
+    let zero_data = ContractError::ZeroData("deposit".to_string(), alloy::sol_types::Error::Overrun.into());
+    assert!(matches!(TaskError::from(zero_data), TaskError::RpcUnavailable(_)));
+    let garbled = ContractError::from(alloy::sol_types::Error::Overrun);
+    assert!(matches!(TaskError::from(garbled), TaskError::RpcUnavailable(_)));
+}
+
+#[test]
+fn test_already_reverts_become_race_lost() {
+    let already = TaskError::Reverted { reason: "Claim already made.".to_string() };
+    assert!(matches!(already.or_race_lost(), TaskError::RaceLost));
+    let challenged = TaskError::Reverted { reason: "Claim already challenged.".to_string() };
+    assert!(matches!(challenged.or_race_lost(), TaskError::RaceLost));
+    for reason in ["Invalid claim.", "Already spent", "Message already relayed or unknown."] {
+        let other = TaskError::Reverted { reason: reason.to_string() };
+        assert!(matches!(other.or_race_lost(), TaskError::Reverted { .. }), "{}", reason);
+    }
+}
@@ -8,8 +8,12 @@ use vea_validator::{
     contracts::{IVeaInboxArbToEth, IVeaOutboxArbToEth},
     config::ValidatorConfig,
     epoch_watcher::EpochWatcher,
+    indexer::EventIndexer,
+    tasks::dispatcher::TaskDispatcher,
+    tasks::fees::FeeBid,
     tasks::{TaskStore, ClaimStore},
 };
+use tokio_util::sync::CancellationToken;
 use common::{restore_pristine, advance_time, send_messages};
 use alloy::providers::Provider;
 
@@ -159,6 +163,42 @@ async fn test_claim_race_condition() {
     let test_dir = tempfile::tempdir().unwrap();
     let claim_store = Arc::new(Mutex::new(ClaimStore::new(test_dir.path().join("claims.json"))));
     let ts = outbox_provider.get_block_by_number(Default::default()).await.unwrap().unwrap().header.timestamp;
-    let result = vea_validator::tasks::claim::execute(route, epoch, &claim_store, ts).await;
+    let result = vea_validator::tasks::claim::execute(route, epoch, &claim_store, ts, FeeBid::default()).await;
     assert!(result.is_ok(), "Validator should handle existing claim gracefully");
 }
+
+#[tokio::test]
+#[serial]
+async fn test_components_stop_on_shutdown() {
+    let c = ValidatorConfig::from_env().unwrap();
+    let route = &c.build_routes()[0];
+    restore_pristine().await;
+
+    let inbox = IVeaInboxArbToEth::new(route.inbox_address, route.inbox_provider.clone());
+    let epoch_period: u64 = inbox.epochPeriod().call().await.unwrap().try_into().unwrap();
+
+    let test_dir = tempfile::tempdir().unwrap();
+    let task_store = Arc::new(Mutex::new(TaskStore::new(test_dir.path().join("schedule.json"))));
+    let claim_store = Arc::new(Mutex::new(ClaimStore::new(test_dir.path().join("claims.json"))));
+    let wallet_address = c.wallet.default_signer().address();
+    let shutdown = CancellationToken::new();
+
+    let watcher = EpochWatcher::new(route.clone(), false, claim_store.clone(), task_store.clone())
+        .with_shutdown(shutdown.clone());
+    let indexer = EventIndexer::new(route.clone(), wallet_address, task_store.clone(), claim_store.clone())
+        .with_shutdown(shutdown.clone());
+    let dispatcher = TaskDispatcher::new(c.clone(), route.clone(), task_store.clone(), claim_store.clone())
+        .with_shutdown(shutdown.clone());
+    indexer.initialize().await.unwrap();
+
+    let handle = tokio::spawn(async move {
+        let (watcher_result, _, _) = tokio::join!(watcher.watch_epochs(epoch_period), indexer.run(), dispatcher.run());
+        watcher_result.unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    shutdown.cancel();
+
+    timeout(Duration::from_secs(30), handle).await
+        .expect("Components did not stop after shutdown")
+        .unwrap();
+}